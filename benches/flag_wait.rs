@@ -0,0 +1,53 @@
+use atoman::prelude::*;
+use std::time::{Duration, Instant};
+
+static READY: Flag = Flag::new();
+
+const THREADS: usize = 4;
+const IDLE: Duration = Duration::from_secs(2);
+
+/// Returns the process CPU time (user + system) from '/proc/self/stat'
+fn cpu_time() -> Option<Duration> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+
+    // skip the 'pid (comm)' prefix, the comm may contain spaces:
+    let fields = stat
+        .rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+
+    // the clock ticks are 100 per second on most Linux systems:
+    Some(Duration::from_millis((utime + stime) * 10))
+}
+
+fn main() {
+    let cpu_start = cpu_time();
+    let start = Instant::now();
+
+    // park the threads on the flag:
+    let threads = (0..THREADS)
+        .map(|_| std::thread::spawn(|| READY.blocking_wait(true)))
+        .collect::<Vec<_>>();
+
+    std::thread::sleep(IDLE);
+    READY.set(true);
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let elapsed = start.elapsed();
+    match (cpu_start, cpu_time()) {
+        (Some(cpu_start), Some(cpu_end)) => {
+            let cpu = cpu_end.saturating_sub(cpu_start);
+            println!(
+                "{THREADS} threads idle-waiting for {elapsed:?}: CPU time {cpu:?} ({:.1}% of one core)",
+                cpu.as_secs_f64() / elapsed.as_secs_f64() * 100.0
+            );
+        }
+        _ => println!("CPU time is not available on this platform"),
+    }
+}
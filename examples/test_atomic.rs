@@ -0,0 +1,32 @@
+use atoman::prelude::*;
+
+static REQUESTS: Counter = Counter::new();
+static PEAK: Atomic<i32> = Atomic::new();
+
+#[tokio::main]
+async fn main() {
+    assert_eq!(REQUESTS.get(), 0);
+    assert!(REQUESTS == 0);
+
+    assert_eq!(REQUESTS.add(5), 0);
+    assert_eq!(REQUESTS.sub(2), 5);
+    assert_eq!(REQUESTS.get(), 3);
+
+    assert_eq!(PEAK.fetch_max(10), 0);
+    assert_eq!(PEAK.fetch_max(7), 10);
+    assert_eq!(PEAK.fetch_min(-1), 10);
+    assert_eq!(PEAK.compare_exchange(-1, 1), Ok(-1));
+    assert_eq!(PEAK.to_string(), "1");
+
+    // wait until other tasks reach the goal:
+    for _ in 0..7 {
+        tokio::spawn(async {
+            REQUESTS.add(1);
+        });
+    }
+    REQUESTS.wait_until(|n| n >= 10).await;
+
+    std::thread::spawn(|| REQUESTS.add(10));
+    REQUESTS.blocking_wait_until(|n| n >= 20);
+    assert_eq!(REQUESTS.get(), 20);
+}
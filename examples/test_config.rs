@@ -1,9 +1,11 @@
 #![cfg(feature = "config")]
 use atoman::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, sleep, timeout};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     #[derive(Debug, Clone, Serialize, Deserialize)]
     struct Person {
         name: String,
@@ -28,5 +30,105 @@ async fn main() -> Result<()> {
     cfg.age = 24;
     assert_eq!(cfg.age, 24);
 
+    // the update skips unmodified file:
+    let mut cfg = Config::<Person>::new(".test/watch.toml").await?;
+    assert!(!cfg.update().await?);
+    cfg.age = 30;
+    cfg.save().await?;
+    assert!(!cfg.update().await?);
+
+    // the watcher reloads changed file:
+    let changes = Arc::new(Mutex::new(vec![]));
+    let watcher = cfg.watch(Duration::from_millis(10));
+    watcher.on_change({
+        let changes = changes.clone();
+        move |old: &Person, new: &Person| changes.lock().unwrap().push((old.age, new.age))
+    });
+    let mut rx = watcher.subscribe();
+
+    // the callback can register other callbacks without deadlock:
+    let watcher = Arc::new(watcher);
+    let nested = Arc::new(Mutex::new(0));
+    watcher.on_change({
+        let watcher = Arc::downgrade(&watcher);
+        let nested = nested.clone();
+        move |_: &Person, _: &Person| {
+            if let Some(watcher) = watcher.upgrade() {
+                let nested = nested.clone();
+                watcher.on_change(move |_: &Person, _: &Person| *nested.lock().unwrap() += 1);
+            }
+        }
+    });
+
+    sleep(Duration::from_millis(20)).await;
+    std::fs::write(".test/watch.toml", "name = \"Alice\"\nage = 31\n")?;
+    timeout(Duration::from_secs(2), rx.changed()).await?;
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(watcher.get().await.name, "Alice");
+    assert_eq!(*changes.lock().unwrap(), [(30, 31)]);
+    assert_eq!(*nested.lock().unwrap(), 0);
+
+    // the same contents are not a change:
+    std::fs::write(".test/watch.toml", "name = \"Alice\"\nage = 31\n")?;
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(changes.lock().unwrap().len(), 1);
+
+    // the watcher stops on drop:
+    let state = watcher.state();
+    drop(watcher);
+    std::fs::write(".test/watch.toml", "name = \"Bob\"\nage = 32\n")?;
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(state.get().await.age, 31);
+
+    // the atomic writing with backup:
+    let _ = std::fs::remove_file(".test/backup.toml");
+    let mut cfg = Config::<Person>::new(".test/backup.toml")
+        .await?
+        .with_backup(true);
+    cfg.age = 40;
+    cfg.save().await?;
+    cfg.age = 41;
+    cfg.save().await?;
+    assert!(std::fs::read_to_string(".test/backup.toml.bak")?.contains("age = 40"));
+    assert!(
+        std::fs::read_dir(".test")?.all(|e| !e.unwrap().path().to_string_lossy().ends_with(".tmp"))
+    );
+
+    // the truncated file falls back to backup:
+    std::fs::write(".test/backup.toml", "name = \"Bo")?;
+    let cfg = Config::<Person>::read(".test/backup.toml").await?;
+    assert_eq!(cfg.age, 40);
+
+    // the invalid file doesn't fall back to backup:
+    std::fs::write(".test/backup.toml", "name = \"Bob\"\nage = 20\n")?;
+    let invalid = Config::<Person>::default()
+        .with_validator(|p| match p.age {
+            30.. => Ok(()),
+            _ => Err("too young".to_owned()),
+        })
+        .load(".test/backup.toml")
+        .await;
+    assert!(invalid.unwrap_err().to_string().contains("too young"));
+
+    // the broken backup returns the original error:
+    let broken = "name = \"Bo";
+    std::fs::write(".test/backup.toml", broken)?;
+    std::fs::write(".test/backup.toml.bak", "name = \"Bob\"\nage = \n")?;
+    assert_eq!(
+        Config::<Person>::read(".test/backup.toml")
+            .await
+            .unwrap_err()
+            .to_string(),
+        Config::<Person>::parse(".test/backup.toml", broken)
+            .unwrap_err()
+            .to_string()
+    );
+
+    std::fs::remove_file(".test/backup.toml.bak")?;
+    assert!(Config::<Person>::read(".test/backup.toml").await.is_err());
+    std::fs::remove_file(".test/backup.toml")?;
+    std::fs::remove_file(".test/watch.toml")?;
+
     Ok(())
 }
@@ -0,0 +1,72 @@
+use atoman::prelude::*;
+use std::sync::Arc;
+
+static CONFIG: State<Config> = State::new();
+static SCALE: State<u32> = State::with_init(|| 2);
+
+#[derive(Default, Clone)]
+pub struct Config {
+    pub workers: u32,
+}
+
+#[tokio::main]
+async fn main() {
+    let workers = State::derive(&CONFIG, |cfg| cfg.workers);
+    let threads = State::derive2(&CONFIG, &SCALE, |cfg, scale| cfg.workers * scale);
+    let label = State::derive(&threads, |threads| format!("{threads} threads"));
+    assert_eq!(*workers.get().await, 0);
+
+    let mut rx = threads.subscribe();
+    CONFIG.map(|cfg| cfg.workers = 4).await;
+    assert_eq!(*rx.changed().await.unwrap(), 8);
+    assert_eq!(*workers.get().await, 4);
+
+    SCALE.blocking_set(3);
+    assert_eq!(*threads.blocking_get(), 12);
+    assert_eq!(*label.dirty_get(), "12 threads");
+
+    // combine more sources into a tuple:
+    let both = State::combine(&workers, &SCALE);
+    let total = State::derive(&both, |(workers, scale)| **workers + **scale);
+    assert_eq!(*total.get().await, 7);
+
+    // three sources:
+    let offset = State::from(1u32);
+    let sum = State::derive3(&CONFIG, &SCALE, &offset, |cfg, scale, offset| {
+        cfg.workers * scale + offset
+    });
+    assert_eq!(*sum.get().await, 13);
+    offset.set(2).await;
+    assert_eq!(*sum.get().await, 14);
+
+    // any number of sources of the same type:
+    let counts = [State::from(1u32), State::from(2), State::from(3)];
+    let sources: Vec<&dyn DeriveSource<u32>> = counts.iter().map(|s| s as _).collect();
+    let max = State::derive_all(&sources, |values| values.iter().map(|v| **v).max());
+    assert_eq!(*max.get().await, Some(3));
+    counts[0].set(10).await;
+    assert_eq!(*max.get().await, Some(10));
+
+    // the concurrent source changes don't publish a stale value:
+    let a = Arc::new(State::from(0u64));
+    let b = Arc::new(State::from(0u64));
+    let both = State::derive2(&*a, &*b, |a, b| {
+        // the slow computing widens the race window:
+        std::thread::sleep(std::time::Duration::from_micros(50));
+        (*a, *b)
+    });
+    let threads: Vec<_> = [a.clone(), b.clone()]
+        .into_iter()
+        .map(|state| {
+            std::thread::spawn(move || {
+                for i in 1..=300 {
+                    state.blocking_set(i);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(*both.dirty_get(), (300, 300));
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "diagnostics")]
+use atoman::log::{self, Log, Metadata, Record};
+use atoman::prelude::*;
+use std::sync::Mutex;
+use tokio::time::{Duration, sleep};
+
+static COUNT: State<i32> = State::new();
+static COUNTS: StateMap<&str, i32> = StateMap::new();
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Collects the warnings
+struct Collector;
+
+impl Log for Collector {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        WARNINGS.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+fn take_warnings() -> Vec<String> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap())
+}
+
+#[tokio::main]
+async fn main() {
+    log::set_logger(&Collector).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    Diagnostics::set_hold_threshold(Duration::from_millis(50));
+    Diagnostics::set_wait_threshold(Duration::from_millis(50));
+    assert_eq!(Diagnostics::hold_threshold(), Duration::from_millis(50));
+
+    // the guard remembers where it was acquired:
+    let line = line!() + 1;
+    let guard = COUNT.lock().await;
+    assert_eq!(guard.location().file(), file!());
+    assert_eq!(guard.location().line(), line);
+    drop(guard);
+
+    let guard = COUNT.blocking_lock();
+    assert_eq!(guard.location().line(), line!() - 1);
+    drop(guard);
+    assert!(take_warnings().is_empty());
+
+    // the long holding warning:
+    let guard = COUNT.lock().await;
+    let location = guard.location().to_string();
+    sleep(Duration::from_millis(80)).await;
+    drop(guard);
+
+    let warnings = take_warnings();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|w| w.contains(&location)));
+    assert!(warnings.iter().any(|w| w.contains("not released yet")));
+    assert!(warnings.iter().any(|w| w.contains("was held for")));
+
+    // the map & projection guards remember the caller location:
+    let line = line!() + 1;
+    let guard = COUNTS.entry("a", || 0).await;
+    assert_eq!(
+        (guard.location().file(), guard.location().line()),
+        (file!(), line)
+    );
+    drop(guard);
+
+    let guard = COUNTS.lock("a").await.unwrap();
+    assert_eq!(guard.location().line(), line!() - 1);
+    drop(guard);
+
+    let projection = COUNT.project_mut(|n| n, |n| n);
+    let guard = projection.lock().await;
+    let location = format!("{}:{}", file!(), line!() - 1);
+    let waiter = tokio::spawn(async { *COUNT.view().get().await });
+    sleep(Duration::from_millis(80)).await;
+    drop(guard);
+    waiter.await.unwrap();
+
+    // the view waiting is diagnosed too:
+    let warnings = take_warnings();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("waits for state unlock") && w.contains(&location))
+    );
+
+    // the long waiting warning:
+    let mut guard = COUNT.lock().await;
+    let location = guard.location().to_string();
+    let waiter = tokio::spawn(async { *COUNT.get().await });
+    sleep(Duration::from_millis(80)).await;
+    *guard = 5;
+    drop(guard);
+    assert_eq!(waiter.await.unwrap(), 5);
+
+    let warnings = take_warnings();
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("waits for state unlock") && w.contains(&location))
+    );
+    assert!(warnings.iter().any(|w| w.contains("was held for")));
+
+    // the blocking waiting warning:
+    let guard = COUNT.lock().await;
+    let waiter = std::thread::spawn(|| COUNT.blocking_map(|n| *n += 1));
+    sleep(Duration::from_millis(80)).await;
+    drop(guard);
+    waiter.join().unwrap();
+    assert_eq!(*COUNT.get().await, 6);
+    assert!(
+        take_warnings()
+            .iter()
+            .any(|w| w.contains("waits for state unlock"))
+    );
+
+    // short locks are quiet:
+    COUNT.map(|n| *n += 1).await;
+    COUNT.set(10).await;
+    assert!(take_warnings().is_empty());
+
+    println!("diagnostics ok");
+}
@@ -15,4 +15,23 @@ async fn main() {
 
     IS_ACTIVE.blocking_swap(true);
     assert!(IS_ACTIVE.get());
+
+    assert_eq!(IS_ACTIVE.compare_exchange(true, false), Ok(true));
+    assert_eq!(IS_ACTIVE.compare_exchange(true, false), Err(false));
+
+    assert!(!IS_ACTIVE.toggle());
+    assert!(IS_ACTIVE.fetch_set(false));
+    assert!(IS_ACTIVE.is_false());
+
+    assert!(IS_ACTIVE.try_acquire());
+    assert!(!IS_ACTIVE.try_acquire());
+
+    let timeout = std::time::Duration::from_millis(10);
+    assert!(!IS_ACTIVE.wait_timeout(false, timeout).await);
+    assert!(IS_ACTIVE.wait_timeout(true, timeout).await);
+    assert!(
+        !IS_ACTIVE
+            .wait_or_cancel(false, tokio::time::sleep(timeout))
+            .await
+    );
 }
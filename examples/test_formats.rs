@@ -0,0 +1,170 @@
+#![cfg(all(feature = "config", feature = "yaml-config", feature = "ron-config"))]
+use atoman::{error::Error, prelude::*};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+type DynResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Person {
+    name: String,
+    age: u32,
+}
+
+impl ::std::default::Default for Person {
+    fn default() -> Self {
+        Self {
+            name: "Bob".to_owned(),
+            age: 23,
+        }
+    }
+}
+
+static GLOBAL: GlobalConfig<Person> = GlobalConfig::new().with_format::<CompactJson>();
+static PERSISTENT: PersistentState<Person> =
+    PersistentState::new(".test/formats/persistent.txt").with_format::<CompactJson>();
+
+/// The compact JSON format
+struct CompactJson;
+
+impl ConfigFormat for CompactJson {
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> DynResult<D> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    fn serialize<S: Serialize>(data: &S) -> DynResult<String> {
+        Ok(serde_json::to_string(data)?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> DynResult<()> {
+    std::fs::create_dir_all(".test/formats")?;
+
+    // the built-in formats by file extension:
+    for path in [
+        ".test/formats/person.yaml",
+        ".test/formats/person.yml",
+        ".test/formats/person.ron",
+    ] {
+        let _ = std::fs::remove_file(path);
+
+        let mut cfg = Config::<Person>::new(path).await?;
+        assert_eq!(*cfg, Person::default());
+
+        cfg.age = 30;
+        cfg.save().await?;
+        assert_eq!(Config::<Person>::read(path).await?.age, 30);
+    }
+
+    let yaml = std::fs::read_to_string(".test/formats/person.yaml")?;
+    assert!(yaml.contains("name: Bob"));
+    let ron = std::fs::read_to_string(".test/formats/person.ron")?;
+    assert!(ron.contains("age: 30"));
+
+    // the unknown keys rejection is unsupported by YAML & RON:
+    for path in [".test/formats/person.yaml", ".test/formats/person.ron"] {
+        let e = Config::<Person>::default()
+            .with_deny_unknown(true)
+            .load(path)
+            .await
+            .unwrap_err();
+        assert!(matches!(*e.downcast::<Error>()?, Error::ConfigStrict));
+    }
+
+    // the broken files of any format fall back to backup:
+    for (path, broken) in [
+        (".test/formats/backup.yaml", "name: [Bob"),
+        (".test/formats/backup.ron", "(name: \"Bo"),
+    ] {
+        let _ = std::fs::remove_file(path);
+
+        let mut cfg = Config::<Person>::new(path).await?.with_backup(true);
+        cfg.age = 40;
+        cfg.save().await?;
+        cfg.age = 41;
+        cfg.save().await?;
+
+        std::fs::write(path, broken)?;
+        assert_eq!(Config::<Person>::read(path).await?.age, 40);
+    }
+
+    let path = ".test/formats/backup.txt";
+    std::fs::write(format!("{path}.bak"), r#"{"name":"Bob","age":40}"#)?;
+    std::fs::write(path, r#"{"name":"Bo"#)?;
+    assert_eq!(
+        Config::<Person>::read_with::<CompactJson, _>(path)
+            .await?
+            .age,
+        40
+    );
+
+    // the unsupported extension:
+    assert!(Config::<Person>::parse("person.ini", "").is_err());
+
+    // the custom format:
+    let path = ".test/formats/person.txt";
+    let _ = std::fs::remove_file(path);
+
+    let mut cfg = Config::<Person>::new_with::<CompactJson, _>(path).await?;
+    assert_eq!(std::fs::read_to_string(path)?, r#"{"name":"Bob","age":23}"#);
+
+    cfg.name = "Alice".to_owned();
+    cfg.save().await?;
+    let cfg = Config::<Person>::read_with::<CompactJson, _>(path).await?;
+    assert_eq!(cfg.name, "Alice");
+
+    let cfg = Config::<Person>::parse_with::<CompactJson, _>(path, r#"{"name":"Eve","age":1}"#)?;
+    assert_eq!(cfg.age, 1);
+
+    let mut cfg = Config::<Person>::from(Person::default()).with_format::<CompactJson>();
+    cfg.write(".test/formats/other.txt").await?;
+    assert!(std::fs::read_to_string(".test/formats/other.txt")?.starts_with('{'));
+
+    // the custom format of global & persistent configs:
+    GLOBAL.init(".test/formats/global.txt").await?;
+    let mut guard = GLOBAL.lock().await;
+    guard.age = 50;
+    guard.save().await?;
+    assert_eq!(
+        std::fs::read_to_string(".test/formats/global.txt")?,
+        r#"{"name":"Bob","age":50}"#
+    );
+
+    PERSISTENT
+        .set(Person {
+            name: "Dan".to_owned(),
+            age: 60,
+        })
+        .await;
+    PERSISTENT.flush().await?;
+    assert_eq!(
+        std::fs::read_to_string(".test/formats/persistent.txt")?,
+        r#"{"name":"Dan","age":60}"#
+    );
+
+    // the custom format of layered config files:
+    std::fs::write(".test/formats/layer.txt", r#"{"age":70}"#)?;
+    let mut cfg = Config::<Person>::builder()
+        .defaults()
+        .file_with::<CompactJson, _>(".test/formats/layer.txt")
+        .optional_file_with::<CompactJson, _>(".test/formats/missing.txt")
+        .build()
+        .await?;
+    assert_eq!(
+        *cfg,
+        Person {
+            name: "Bob".to_owned(),
+            age: 70
+        }
+    );
+
+    cfg.name = "Kim".to_owned();
+    cfg.save().await?;
+    assert_eq!(
+        std::fs::read_to_string(".test/formats/layer.txt")?,
+        r#"{"name":"Kim","age":70}"#
+    );
+
+    std::fs::remove_dir_all(".test/formats")?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "config")]
+use atoman::prelude::*;
+use serde::{Deserialize, Serialize};
+
+static SETTINGS: GlobalConfig<Settings> = GlobalConfig::new();
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    name: String,
+    workers: u32,
+}
+
+impl ::std::default::Default for Settings {
+    fn default() -> Self {
+        Self {
+            name: "main".to_owned(),
+            workers: 4,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = ".test/global.toml";
+    let _ = std::fs::remove_file(path);
+
+    // the default value before initialization:
+    assert!(!SETTINGS.is_init());
+    assert_eq!(SETTINGS.get().await.workers, 4);
+    assert!(SETTINGS.save().await.is_err());
+
+    // changes of a failed save aren't published:
+    let mut guard = SETTINGS.lock().await;
+    guard.workers = 16;
+    assert!(guard.save().await.is_err());
+    assert_eq!(SETTINGS.get().await.workers, 4);
+
+    // creates the default config file:
+    SETTINGS.init(path).await?;
+    assert!(SETTINGS.is_init());
+    assert!(std::path::Path::new(path).exists());
+
+    // saving through the guard:
+    let mut guard = SETTINGS.lock().await;
+    guard.workers = 8;
+    guard.save().await?;
+    assert_eq!(SETTINGS.get().await.workers, 8);
+
+    let cfg = Config::<Settings>::read(path).await?;
+    assert_eq!(cfg.workers, 8);
+
+    // aborted changes are not saved:
+    let mut guard = SETTINGS.lock().await;
+    guard.name = "other".to_owned();
+    guard.abort();
+    assert_eq!(SETTINGS.get().await.name, "main");
+
+    // the plain state guard changes memory only:
+    SETTINGS.map(|s| s.workers = 16).await;
+    assert_eq!(Config::<Settings>::read(path).await?.workers, 8);
+    SETTINGS.save().await?;
+    assert_eq!(Config::<Settings>::read(path).await?.workers, 16);
+
+    // reads the existing config file:
+    static OTHER: GlobalConfig<Settings> = GlobalConfig::new();
+    OTHER.init(path).await?;
+    assert_eq!(*OTHER.get().await, *SETTINGS.get().await);
+
+    std::fs::remove_file(path)?;
+
+    Ok(())
+}
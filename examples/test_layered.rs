@@ -0,0 +1,184 @@
+#![cfg(feature = "config")]
+use atoman::{error::Error, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Settings {
+    name: String,
+    debug: bool,
+    server: Server,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+    tags: Vec<String>,
+}
+
+impl ::std::default::Default for Settings {
+    fn default() -> Self {
+        Self {
+            name: "app".to_owned(),
+            debug: false,
+            server: Server {
+                host: "127.0.0.1".to_owned(),
+                port: 80,
+                tags: vec![],
+            },
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    std::fs::create_dir_all(".test/layered")?;
+    std::fs::write(
+        ".test/layered/base.toml",
+        "name = \"base\"\n[server]\nhost = \"0.0.0.0\"\ntags = [\"a\"]\n",
+    )?;
+    std::fs::write(
+        ".test/layered/host.json",
+        r#"{ "server": { "port": 8000 } }"#,
+    )?;
+
+    unsafe {
+        std::env::set_var("TEST_LAYERED_SERVER__PORT", "8080");
+        std::env::set_var("TEST_LAYERED_NAME", "from env");
+        std::env::set_var("TEST_LAYERED_SERVER__TAGS", r#"["x", "y"]"#);
+    }
+
+    // the layers are merged in order:
+    let cfg = Config::<Settings>::builder()
+        .defaults()
+        .file(".test/layered/base.toml")
+        .optional_file(".test/layered/host.json")
+        .optional_file(".test/layered/missing.toml")
+        .env("TEST_LAYERED_", "__")
+        .build()
+        .await?;
+
+    assert_eq!(cfg.name, "from env");
+    assert!(!cfg.debug);
+    assert_eq!(cfg.server.host, "0.0.0.0");
+    assert_eq!(cfg.server.port, 8080);
+    assert_eq!(cfg.server.tags, ["x", "y"]);
+    assert_eq!(cfg.path(), &PathBuf::from(".test/layered/host.json"));
+
+    // the sources of keys:
+    assert_eq!(cfg.source("debug"), Some(&ConfigSource::Default));
+    assert_eq!(
+        cfg.source("server.host"),
+        Some(&ConfigSource::File(".test/layered/base.toml".into()))
+    );
+    assert_eq!(
+        cfg.source("server.port"),
+        Some(&ConfigSource::Env("TEST_LAYERED_SERVER__PORT".to_owned()))
+    );
+    assert_eq!(cfg.source("server"), None);
+    assert_eq!(cfg.sources().len(), 5);
+    println!("{}", cfg.source("name").unwrap());
+
+    // the files only:
+    let cfg = Config::<Settings>::builder()
+        .defaults()
+        .file(".test/layered/base.toml")
+        .file(".test/layered/host.json")
+        .path(".test/layered/saved.toml")
+        .build()
+        .await?;
+    assert_eq!(cfg.server.port, 8000);
+    assert_eq!(cfg.path(), &PathBuf::from(".test/layered/saved.toml"));
+
+    // the missing required file & keys:
+    assert!(
+        Config::<Settings>::builder()
+            .file(".test/layered/missing.toml")
+            .build()
+            .await
+            .is_err()
+    );
+    assert!(
+        Config::<Settings>::builder()
+            .file(".test/layered/host.json")
+            .build()
+            .await
+            .is_err()
+    );
+
+    // the env JSON scalars are kept as strings for string fields:
+    unsafe {
+        std::env::set_var("TEST_SCALARS_NAME", "12345");
+        std::env::set_var("TEST_SCALARS_SERVER__HOST", "null");
+        std::env::set_var("TEST_SCALARS_DEBUG", "true");
+
+        // the non-unicode variables are skipped:
+        #[cfg(unix)]
+        std::env::set_var(
+            "TEST_SCALARS_BROKEN",
+            <std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(b"\xff"),
+        );
+    }
+    let cfg = Config::<Settings>::builder()
+        .defaults()
+        .env("TEST_SCALARS_", "__")
+        .build()
+        .await?;
+    assert_eq!(cfg.name, "12345");
+    assert_eq!(cfg.server.host, "null");
+    assert!(cfg.debug);
+
+    unsafe { std::env::set_var("TEST_SCALARS_SERVER__PORT", "high") }
+    let err = Config::<Settings>::builder()
+        .defaults()
+        .env("TEST_SCALARS_", "__")
+        .build()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("server.port"));
+    assert!(err.to_string().contains("TEST_SCALARS_SERVER__PORT"));
+
+    // the errors are located in the source files:
+    std::fs::write(".test/layered/broken.toml", "name = \"b")?;
+    let err = Config::<Settings>::builder()
+        .defaults()
+        .file(".test/layered/broken.toml")
+        .build()
+        .await
+        .unwrap_err();
+    match *err.downcast::<Error>()? {
+        Error::ConfigParse { path, line, .. } => {
+            assert_eq!(path, PathBuf::from(".test/layered/broken.toml"));
+            assert_eq!(line, 1);
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+
+    std::fs::write(
+        ".test/layered/typed.toml",
+        "name = \"typed\"\n[server]\nport = \"high\"\n",
+    )?;
+    let err = Config::<Settings>::builder()
+        .defaults()
+        .file(".test/layered/typed.toml")
+        .build()
+        .await
+        .unwrap_err();
+    match *err.downcast::<Error>()? {
+        Error::ConfigParse {
+            path,
+            line,
+            key_path,
+            ..
+        } => {
+            assert_eq!(path, PathBuf::from(".test/layered/typed.toml"));
+            assert_eq!(line, 3);
+            assert_eq!(key_path, "server.port");
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+
+    std::fs::remove_dir_all(".test/layered")?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use atoman::prelude::*;
+use tokio::time::{Duration, sleep};
+
+static COUNTER: State<u64> = State::new();
+
+const TASKS: u64 = 64;
+const THREADS: u64 = 4;
+const INCREMENTS: u64 = 250;
+
+#[tokio::main]
+async fn main() {
+    // concurrent async increments:
+    let tasks = (0..TASKS)
+        .map(|_| {
+            tokio::spawn(async {
+                for _ in 0..INCREMENTS {
+                    COUNTER.map(|v| *v += 1).await;
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // concurrent blocking increments:
+    let threads = (0..THREADS)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..INCREMENTS {
+                    *COUNTER.blocking_lock() += 1;
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(*COUNTER.get().await, (TASKS + THREADS) * INCREMENTS);
+    assert!(!COUNTER.is_locked());
+
+    // timeouts & cancellation while the guard is alive:
+    let timeout = Duration::from_millis(10);
+    let guard = COUNTER.lock().await;
+
+    assert!(COUNTER.lock_timeout(timeout).await.is_none());
+    assert!(COUNTER.blocking_lock_timeout(timeout).is_none());
+    assert!(COUNTER.get_timeout(timeout).await.is_none());
+    assert!(COUNTER.get_or_cancel(sleep(timeout)).await.is_none());
+    drop(guard);
+
+    assert!(COUNTER.lock_timeout(timeout).await.is_some());
+    assert!(COUNTER.blocking_get_timeout(timeout).is_some());
+}
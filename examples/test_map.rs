@@ -0,0 +1,88 @@
+use atoman::prelude::*;
+use std::{
+    pin::pin,
+    task::{Context, Waker},
+};
+use tokio::time::{Duration, timeout};
+
+static SESSIONS: StateMap<String, Session> = StateMap::new();
+
+#[derive(Default, Clone, Debug)]
+pub struct Session {
+    pub hits: u32,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert!(
+        SESSIONS
+            .insert("alice".into(), Session::default())
+            .await
+            .is_none()
+    );
+    assert!(
+        SESSIONS
+            .blocking_insert("bob".into(), Session { hits: 5 })
+            .is_none()
+    );
+    assert_eq!(SESSIONS.len(), 2);
+
+    // per-key notifications:
+    let mut rx = SESSIONS.subscribe("alice").unwrap();
+    assert!(SESSIONS.update("alice", |s| s.hits += 1).await);
+    assert_eq!(rx.changed().await.unwrap().hits, 1);
+    assert!(!SESSIONS.update("carol", |s| s.hits += 1).await);
+
+    // entry-style locking:
+    SESSIONS.entry("carol".into(), Session::default).await.hits = 3;
+    SESSIONS.blocking_entry("bob".into(), Session::default).hits += 1;
+    assert_eq!(SESSIONS.get("carol").await.unwrap().hits, 3);
+    assert_eq!(SESSIONS.blocking_get("bob").unwrap().hits, 6);
+
+    let prev = SESSIONS.insert("bob".into(), Session::default()).await;
+    assert_eq!(prev.unwrap().hits, 6);
+
+    assert_eq!(SESSIONS.remove("carol").await.unwrap().hits, 3);
+    assert!(SESSIONS.dirty_get("carol").is_none());
+
+    let mut keys = SESSIONS.keys();
+    keys.sort();
+    assert_eq!(keys, ["alice", "bob"]);
+
+    // the removal closes the key receivers:
+    let mut rx = SESSIONS.subscribe("alice").unwrap();
+    let waiter = tokio::spawn(async move { (rx.changed().await.is_none(), rx.is_closed()) });
+    tokio::task::yield_now().await;
+    SESSIONS.remove("alice").await;
+    assert_eq!(
+        timeout(Duration::from_secs(1), waiter).await??,
+        (true, true)
+    );
+
+    // the insertion of waiting key:
+    let waiter = tokio::spawn(async { SESSIONS.wait_key("dave").await.borrow_and_update().hits });
+    tokio::task::yield_now().await;
+    SESSIONS.insert("dave".into(), Session { hits: 7 }).await;
+    assert_eq!(timeout(Duration::from_secs(1), waiter).await??, 7);
+
+    let thread = std::thread::spawn(|| SESSIONS.blocking_wait_key("erin").borrow_and_update().hits);
+    SESSIONS.entry("erin".into(), || Session { hits: 9 }).await;
+    assert_eq!(thread.join().unwrap(), 9);
+
+    // the insert into removed key is not lost (the futures are polled once to order them):
+    let guard = SESSIONS.lock("bob").await.unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+
+    let mut insert = pin!(SESSIONS.insert("bob".into(), Session { hits: 42 }));
+    assert!(insert.as_mut().poll(&mut cx).is_pending());
+    let mut remove = pin!(SESSIONS.remove("bob"));
+    assert!(remove.as_mut().poll(&mut cx).is_pending());
+    assert!(!SESSIONS.contains_key("bob"));
+    drop(guard);
+
+    assert_eq!(remove.await.unwrap().hits, 0);
+    assert!(insert.await.is_none());
+    assert_eq!(SESSIONS.get("bob").await.unwrap().hits, 42);
+
+    Ok(())
+}
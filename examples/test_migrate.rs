@@ -0,0 +1,168 @@
+#![cfg(feature = "config")]
+use atoman::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static MIGRATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    version: u64,
+    name: String,
+    workers: u32,
+    server: Server,
+}
+
+impl ::std::default::Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: 2,
+            name: "main".to_owned(),
+            workers: 4,
+            server: Server::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+impl ::std::default::Default for Server {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_owned(),
+            port: 8080,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Unversioned {
+    workers: u32,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    std::fs::create_dir_all(".test/migrate")?;
+    let path = ".test/migrate/settings.toml";
+
+    // the file is not rewritten by default:
+    std::fs::write(path, "name = \"app\"\n")?;
+    let cfg = Config::<Settings>::new(path).await?;
+    assert_eq!((cfg.name.as_str(), cfg.workers), ("app", 4));
+    assert_eq!(std::fs::read_to_string(path)?, "name = \"app\"\n");
+
+    // the new default keys are added with keeping the user values:
+    let cfg = Config::<Settings>::default()
+        .with_rewrite(true)
+        .load(path)
+        .await?;
+    assert_eq!(cfg.name, "app");
+    let contents = std::fs::read_to_string(path)?;
+    assert!(contents.contains("name = \"app\""));
+    assert!(contents.contains("workers = 4"));
+    assert!(contents.contains("port = 8080"));
+
+    // the actual file is not rewritten:
+    let modified = std::fs::metadata(path)?.modified()?;
+    Config::<Settings>::default()
+        .with_rewrite(true)
+        .load(path)
+        .await?;
+    assert_eq!(std::fs::metadata(path)?.modified()?, modified);
+
+    // the versioned migrations:
+    let migrated = || {
+        Config::<Settings>::default()
+            .with_migration(1, |value| {
+                // v1: 'threads' was renamed to 'workers'
+                if let Some(threads) = value.as_object_mut().and_then(|m| m.remove("threads")) {
+                    value["workers"] = threads;
+                }
+            })
+            .with_migration(2, |value| {
+                // v2: 'port' was moved to the 'server' table
+                if let Some(port) = value.as_object_mut().and_then(|m| m.remove("port")) {
+                    value["server"] = serde_json::json!({ "port": port });
+                }
+            })
+    };
+
+    std::fs::write(path, "name = \"old\"\nthreads = 8\nport = 9000\n")?;
+    let cfg = migrated().load(path).await?;
+    assert_eq!(cfg.version, 2);
+    assert_eq!(cfg.workers, 8);
+    assert_eq!(cfg.server.port, 9000);
+    assert_eq!(cfg.server.host, "localhost");
+
+    let cfg = Config::<Settings>::read(path).await?;
+    assert_eq!((cfg.version, cfg.workers, cfg.server.port), (2, 8, 9000));
+    assert!(!std::fs::read_to_string(path)?.contains("threads"));
+
+    // the partially migrated file:
+    std::fs::write(
+        path,
+        "version = 1\nworkers = 2\nport = 9001\nthreads = 16\n",
+    )?;
+    let cfg = migrated().load(path).await?;
+    assert_eq!((cfg.workers, cfg.server.port), (2, 9001));
+
+    // the actual version is not migrated:
+    std::fs::write(path, "version = 2\nport = 1\n")?;
+    let cfg = migrated().load(path).await?;
+    assert_eq!(cfg.server.port, 8080);
+
+    // the new file is created with the actual version:
+    std::fs::remove_file(path)?;
+    let cfg = migrated().load(path).await?;
+    assert_eq!(cfg.version, 2);
+    assert!(std::fs::read_to_string(path)?.contains("version = 2"));
+
+    // the version is kept for data without version field:
+    let counted = || {
+        Config::<Unversioned>::default().with_migration(1, |value| {
+            MIGRATIONS.fetch_add(1, Ordering::SeqCst);
+            if let Some(threads) = value.as_object_mut().and_then(|m| m.remove("threads")) {
+                value["workers"] = threads;
+            }
+        })
+    };
+
+    let path = ".test/migrate/unversioned.toml";
+    std::fs::write(path, "threads = 3\n")?;
+    assert_eq!(counted().load(path).await?.workers, 3);
+    assert!(std::fs::read_to_string(path)?.contains("version = 1"));
+
+    let modified = std::fs::metadata(path)?.modified()?;
+    assert_eq!(counted().load(path).await?.workers, 3);
+    assert_eq!(std::fs::metadata(path)?.modified()?, modified);
+    assert_eq!(MIGRATIONS.load(Ordering::SeqCst), 1);
+
+    // the new file is created with the latest version:
+    std::fs::remove_file(path)?;
+    counted().load(path).await?;
+    assert!(std::fs::read_to_string(path)?.contains("version = 1"));
+    counted().load(path).await?;
+    assert_eq!(MIGRATIONS.load(Ordering::SeqCst), 1);
+
+    // the version key isn't rejected as unknown for data without version field:
+    let strict = || counted().with_deny_unknown(true);
+    std::fs::write(path, "threads = 5\n")?;
+    assert_eq!(strict().load(path).await?.workers, 5);
+    assert!(std::fs::read_to_string(path)?.contains("version = 1"));
+    assert_eq!(strict().load(path).await?.workers, 5);
+    assert_eq!(MIGRATIONS.load(Ordering::SeqCst), 2);
+
+    std::fs::write(path, "version = 1\nworkers = 5\nthreads = 5\n")?;
+    let err = strict().load(path).await.unwrap_err();
+    assert!(err.to_string().contains("'threads'"));
+
+    std::fs::remove_dir_all(".test/migrate")?;
+    Ok(())
+}
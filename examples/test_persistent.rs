@@ -0,0 +1,63 @@
+#![cfg(feature = "config")]
+use atoman::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, sleep};
+
+static PERSON: PersistentState<Person> =
+    PersistentState::new(".test/persistent.toml").with_debounce(Duration::from_millis(10));
+static BROKEN: PersistentState<Person> = PersistentState::new(".test/persistent_broken.toml");
+static THREAD: PersistentState<Person> = PersistentState::new(".test/persistent_thread.toml");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Person {
+    name: String,
+    age: u32,
+}
+
+impl ::std::default::Default for Person {
+    fn default() -> Self {
+        Self {
+            name: "Bob".to_owned(),
+            age: 23,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = tokio::fs::remove_file(PERSON.path()).await;
+    PERSON.init().await.unwrap();
+    assert_eq!(PERSON.get().await.name, "Bob");
+
+    // the changes are written after debounce delay:
+    PERSON.lock().await.age = 30;
+    PERSON.map(|person| person.age += 1).await;
+    sleep(Duration::from_millis(100)).await;
+
+    let contents = tokio::fs::read_to_string(PERSON.path()).await.unwrap();
+    assert!(contents.contains("age = 31"));
+
+    PERSON.dirty_set(Person::default());
+    PERSON.flush().await.unwrap();
+
+    let contents = tokio::fs::read_to_string(PERSON.path()).await.unwrap();
+    assert!(contents.contains("age = 23"));
+    tokio::fs::remove_file(PERSON.path()).await.unwrap();
+
+    // the malformed file fails on init & falls back to default on access:
+    tokio::fs::write(BROKEN.path(), "age = \"old\"")
+        .await
+        .unwrap();
+    assert!(BROKEN.init().await.is_err());
+    assert_eq!(BROKEN.get().await.age, 23);
+    tokio::fs::remove_file(BROKEN.path()).await.unwrap();
+
+    // the access outside of tokio runtime:
+    std::thread::spawn(|| {
+        assert_eq!(THREAD.blocking_get().name, "Bob");
+        THREAD.blocking_set(Person::default());
+    })
+    .join()
+    .unwrap();
+    assert!(!THREAD.path().exists());
+}
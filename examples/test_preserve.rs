@@ -0,0 +1,162 @@
+#![cfg(feature = "config")]
+use atoman::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    name: String,
+    workers: u32,
+    tags: Vec<String>,
+    server: Server,
+    limits: Limits,
+    users: Vec<User>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Server {
+    host: String,
+    port: u16,
+    tls: bool,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Limits {
+    rps: u32,
+    burst: u32,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct User {
+    name: String,
+    admin: bool,
+}
+
+const CONTENTS: &str = r#"# The application settings
+name = "app"    # the service name
+
+# number of worker threads
+workers = 0x10
+tags = [
+    "a", # first
+    "b",
+]
+limits = { rps = 100, burst = 10 }
+
+# The HTTP server
+[server]
+host = "0.0.0.0"
+port = 8080 # public port
+
+[[users]]
+name = "root" # superuser
+admin = true
+
+[[users]]
+name = "guest"
+admin = false
+"#;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    std::fs::create_dir_all(".test/preserve")?;
+    let path = ".test/preserve/settings.toml";
+    std::fs::write(path, CONTENTS)?;
+
+    // the unchanged values keep the file as is (only the new default keys are added):
+    let mut cfg = Config::<Settings>::read(path).await?;
+    assert_eq!(cfg.workers, 16);
+    cfg.save().await?;
+    assert_eq!(
+        std::fs::read_to_string(path)?,
+        CONTENTS.replace("# public port\n", "# public port\ntls = false\n")
+    );
+
+    // the changed values keep the comments, ordering & whitespace:
+    cfg.server.port = 9090;
+    cfg.limits.rps = 200;
+    cfg.tags[1] = "c".to_owned();
+    cfg.users[0].admin = false;
+    cfg.users.pop();
+    cfg.save().await?;
+
+    let contents = std::fs::read_to_string(path)?;
+    println!("{contents}");
+    assert!(
+        contents.starts_with("# The application settings\nname = \"app\"    # the service name\n")
+    );
+    assert!(contents.contains("# number of worker threads\nworkers = 0x10\n"));
+    assert!(contents.contains("    \"a\", # first\n    \"c\",\n]"));
+    assert!(contents.contains("limits = { rps = 200, burst = 10 }"));
+    assert!(
+        contents.contains(
+            "# The HTTP server\n[server]\nhost = \"0.0.0.0\"\nport = 9090 # public port\n"
+        )
+    );
+    assert!(contents.contains("name = \"root\" # superuser\nadmin = false\n"));
+    assert!(!contents.contains("guest"));
+
+    let cfg = Config::<Settings>::read(path).await?;
+    assert_eq!(
+        (cfg.server.port, cfg.limits.rps, cfg.users.len()),
+        (9090, 200, 1)
+    );
+
+    // the new array tables are appended:
+    let mut cfg = cfg;
+    cfg.users.push(User {
+        name: "ops".to_owned(),
+        admin: true,
+    });
+    cfg.save().await?;
+    let cfg = Config::<Settings>::read(path).await?;
+    assert_eq!(cfg.users[1].name, "ops");
+
+    // the resized arrays keep the comments & indents:
+    let mut cfg = cfg;
+    cfg.tags.push("d".to_owned());
+    cfg.save().await?;
+    let contents = std::fs::read_to_string(path)?;
+    assert!(contents.contains("tags = [\n    \"a\", # first\n    \"c\",\n    \"d\",\n]"));
+
+    cfg.tags.truncate(1);
+    cfg.save().await?;
+    let contents = std::fs::read_to_string(path)?;
+    assert!(contents.contains("tags = [\n    \"a\", # first\n]"));
+
+    for (tags, expected) in [
+        (
+            "[\"a\", # first\n \"b\"]",
+            "[\"a\", # first\n \"b\",\n \"c\"]",
+        ),
+        ("[\"a\", \"b\"]", "[\"a\", \"b\", \"c\"]"),
+        ("[\"a\"]", "[\"a\", \"b\", \"c\"]"),
+        ("[]", "[\"a\", \"b\", \"c\"]"),
+    ] {
+        std::fs::write(path, format!("tags = {tags}\n"))?;
+        let mut cfg = Config::<Settings>::read(path).await?;
+        cfg.tags = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+        cfg.save().await?;
+        let contents = std::fs::read_to_string(path)?;
+        assert!(contents.starts_with(&format!("tags = {expected}\n")));
+    }
+
+    // the new file is generated:
+    std::fs::remove_file(path)?;
+    let cfg = Config::<Settings>::new(path).await?;
+    assert_eq!(
+        std::fs::read_to_string(path)?,
+        toml::to_string_pretty(&*cfg)?
+    );
+
+    // the broken file is regenerated:
+    std::fs::write(path, "name = \"br")?;
+    let mut cfg = cfg;
+    cfg.save().await?;
+    assert!(Config::<Settings>::read(path).await.is_ok());
+
+    std::fs::remove_dir_all(".test/preserve")?;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use atoman::prelude::*;
+use tokio::time::{Duration, sleep};
+
+static JOBS: StateQueue<u32> = StateQueue::new();
+static RECENT: StateQueue<u32> = StateQueue::bounded(2, Overflow::DropOldest);
+static LATEST: StateQueue<u32> = StateQueue::bounded(2, Overflow::DropNewest);
+static BACKLOG: StateQueue<u32> = StateQueue::bounded(1, Overflow::Block);
+
+#[tokio::main]
+async fn main() {
+    // async pop waits until available:
+    tokio::spawn(async {
+        sleep(Duration::from_millis(10)).await;
+        JOBS.push(1).await;
+    });
+    assert_eq!(JOBS.pop().await, 1);
+
+    std::thread::spawn(|| JOBS.blocking_push(2));
+    assert_eq!(JOBS.blocking_pop(), 2);
+    assert!(JOBS.try_pop().is_none());
+
+    // overflow policies:
+    assert!(RECENT.extend([1, 2]).await.is_empty());
+    assert_eq!(RECENT.push(3).await, Some(1));
+    assert_eq!(RECENT.drain(), [2, 3]);
+
+    assert!(LATEST.extend([1, 2]).await.is_empty());
+    assert_eq!(LATEST.push(3).await, Some(3));
+    assert_eq!(LATEST.drain(), [1, 2]);
+
+    BACKLOG.push(1).await;
+    assert_eq!(BACKLOG.try_push(2), Err(2));
+    tokio::spawn(async {
+        sleep(Duration::from_millis(10)).await;
+        BACKLOG.pop().await;
+    });
+    assert_eq!(BACKLOG.push(2).await, None);
+    assert_eq!(BACKLOG.drain(), [2]);
+}
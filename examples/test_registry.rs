@@ -0,0 +1,83 @@
+#![cfg(feature = "registry")]
+use atoman::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct Limits {
+    max_users: u32,
+    name: String,
+}
+
+atoman::register! {
+    fn register_statics;
+    static IS_ACTIVE: Flag = Flag::new();
+    static REQUESTS: Counter = Counter::new();
+    static LIMITS: State<Limits> = State::new();
+    static TITLE: State<String> = State::from("Atoman".to_owned());
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // registers on first access:
+    assert!(!Registry::contains("IS_ACTIVE"));
+    IS_ACTIVE.set(true);
+    assert!(Registry::contains("IS_ACTIVE"));
+    assert!(!Registry::contains("LIMITS"));
+
+    // registers all eagerly:
+    register_statics();
+    REQUESTS.add(3);
+    assert_eq!(
+        Registry::names(),
+        ["IS_ACTIVE", "LIMITS", "REQUESTS", "TITLE"]
+    );
+
+    // reading as JSON:
+    assert_eq!(Registry::get_json("IS_ACTIVE")?, json!(true));
+    assert_eq!(Registry::get_json("REQUESTS")?, json!(3));
+    assert_eq!(
+        Registry::dump()?,
+        json!({
+            "IS_ACTIVE": true,
+            "LIMITS": { "max_users": 0, "name": "" },
+            "REQUESTS": 3,
+            "TITLE": "Atoman",
+        })
+    );
+
+    // setting from strings:
+    Registry::set("IS_ACTIVE", "false").await?;
+    assert!(IS_ACTIVE.is_false());
+
+    Registry::blocking_set("REQUESTS", "10")?;
+    assert_eq!(REQUESTS.get(), 10);
+
+    Registry::set("LIMITS", r#"{ "max_users": 5, "name": "main" }"#).await?;
+    assert_eq!(
+        *LIMITS.get().await,
+        Limits {
+            max_users: 5,
+            name: "main".to_owned()
+        }
+    );
+
+    Registry::set("TITLE", "plain text").await?;
+    assert_eq!(*TITLE.get().await, "plain text");
+
+    // the JSON scalars are kept as strings for string entries:
+    for text in ["123", "true", "null", "\"quoted\""] {
+        Registry::blocking_set("TITLE", text)?;
+        assert_eq!(*TITLE.get().await, text.trim_matches('"'));
+    }
+
+    // errors:
+    assert!(Registry::set("REQUESTS", "-1").await.is_err());
+    assert!(Registry::get_json("UNKNOWN").is_err());
+
+    assert!(Registry::unregister("TITLE"));
+    assert!(!Registry::contains("TITLE"));
+
+    println!("registry ok");
+    Ok(())
+}
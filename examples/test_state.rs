@@ -1,14 +1,22 @@
 use atoman::prelude::*;
 
 static CONFIG: State<Config> = State::new();
+static LIMITS: State<Limits> = State::with_init(|| Limits { max: 100 });
 
 #[derive(Default, Clone)]
 pub struct Config {
     pub count: i32,
 }
 
+#[derive(Clone)]
+pub struct Limits {
+    pub max: u32,
+}
+
 #[tokio::main]
 async fn main() {
+    assert_eq!(LIMITS.get().await.max, 100);
+
     CONFIG.set(Config { count: 10 }).await;
     assert_eq!(CONFIG.get().await.count, 10);
 
@@ -23,4 +31,83 @@ async fn main() {
 
     CONFIG.lock().await.count = 30;
     assert_eq!(CONFIG.get().await.count, 30);
+
+    let prev = CONFIG
+        .rcu(|cfg| Config {
+            count: cfg.count + 1,
+        })
+        .await;
+    assert_eq!(prev.count, 30);
+    assert_eq!(CONFIG.dirty_get().count, 31);
+
+    let actual = CONFIG.dirty_get();
+    assert!(
+        CONFIG
+            .compare_and_set(&actual, Config { count: 32 })
+            .await
+            .is_ok()
+    );
+    assert!(
+        CONFIG
+            .blocking_compare_and_set(&actual, Config { count: 33 })
+            .is_err()
+    );
+    assert_eq!(CONFIG.dirty_get().count, 32);
+
+    // the read-copy-update waits for the state guard (the guard changes are not lost):
+    let mut guard = CONFIG.lock().await;
+    let rcu = tokio::spawn(async {
+        CONFIG
+            .rcu(|cfg| Config {
+                count: cfg.count * 2,
+            })
+            .await
+    });
+    guard.count = 33;
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    drop(guard);
+    assert_eq!(rcu.await.unwrap().count, 33);
+    assert_eq!(CONFIG.dirty_get().count, 66);
+
+    CONFIG.blocking_rcu(|cfg| Config {
+        count: cfg.count - 1,
+    });
+    CONFIG.dirty_rcu(|cfg| Config {
+        count: cfg.count - 1,
+    });
+    assert_eq!(CONFIG.dirty_get().count, 64);
+    CONFIG.dirty_set(Config { count: 32 });
+
+    let mut rx = CONFIG.subscribe();
+    tokio::spawn(async {
+        CONFIG.set(Config { count: 40 }).await;
+    });
+    assert_eq!(rx.changed().await.unwrap().count, 40);
+
+    std::thread::spawn(|| CONFIG.blocking_map(|cfg| cfg.count = 50));
+    assert_eq!(rx.blocking_changed().unwrap().count, 50);
+
+    // failed updates are not published:
+    let result = CONFIG
+        .try_map(|cfg| {
+            cfg.count = -1;
+            Err::<(), _>("invalid count")
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(CONFIG.get().await.count, 50);
+
+    assert_eq!(
+        CONFIG.blocking_try_map(|cfg| Ok::<_, ()>(cfg.count)),
+        Ok(50)
+    );
+
+    let mut guard = CONFIG.lock().await;
+    guard.count = 60;
+    guard.commit();
+    assert_eq!(CONFIG.dirty_get().count, 60);
+
+    guard.count = 70;
+    guard.abort();
+    assert_eq!(CONFIG.get().await.count, 60);
 }
@@ -0,0 +1,128 @@
+#![cfg(feature = "config")]
+use atoman::{error::Error, prelude::*};
+use serde::{Deserialize, Serialize};
+
+type DynResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Settings {
+    name: String,
+    server: Server,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Server {
+    port: u16,
+}
+
+/// Returns the config parsing error fields
+fn parse_error(e: Box<dyn std::error::Error + Send + Sync>) -> (String, usize, usize, String) {
+    match *e.downcast::<Error>().expect("expected atoman error") {
+        Error::ConfigParse {
+            path,
+            line,
+            column,
+            key_path,
+            message,
+        } => {
+            println!("{path:?}: {message}");
+            (key_path, line, column, path.display().to_string())
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> DynResult<()> {
+    std::fs::create_dir_all(".test/validate")?;
+
+    // the wrong type in TOML:
+    let toml = "name = \"app\"\n\n[server]\nport = \"eighty\"\n";
+    let e = Config::<Settings>::parse("settings.toml", toml).unwrap_err();
+    println!("{e}");
+    assert_eq!(
+        parse_error(e),
+        ("server.port".to_owned(), 4, 8, "settings.toml".to_owned())
+    );
+
+    // the syntax error in TOML:
+    let e = Config::<Settings>::parse("settings.toml", "name = \n").unwrap_err();
+    assert_eq!(parse_error(e).1, 1);
+
+    // the wrong type in JSON:
+    let json = "{\n  \"name\": \"app\",\n  \"server\": { \"port\": -1 }\n}";
+    let e = Config::<Settings>::parse("settings.json", json).unwrap_err();
+    let (key_path, line, _, _) = parse_error(e);
+    assert_eq!((key_path.as_str(), line), ("server.port", 3));
+
+    // the trailing characters in JSON:
+    let json = "{ \"name\": \"app\", \"server\": { \"port\": 1 } } x";
+    assert!(Config::<Settings>::parse("settings.json", json).is_err());
+
+    // the unknown keys are ignored by default:
+    let path = ".test/validate/settings.toml";
+    std::fs::write(
+        path,
+        "nmae = \"typo\"\nname = \"app\"\n[server]\nport = 80\n",
+    )?;
+    let cfg = Config::<Settings>::read(path).await?;
+    assert_eq!(cfg.server.port, 80);
+
+    // the unknown keys are rejected:
+    let e = Config::<Settings>::default()
+        .with_deny_unknown(true)
+        .load(path)
+        .await
+        .unwrap_err();
+    assert_eq!(parse_error(e), ("nmae".to_owned(), 1, 1, path.to_owned()));
+
+    // the nested unknown keys are located:
+    let strict = |path: &'static str| {
+        Config::<Settings>::default()
+            .with_deny_unknown(true)
+            .load(path)
+    };
+
+    std::fs::write(
+        path,
+        "name = \"app\"\n\n[server]\nport = 80\n  hots = \"x\"\n",
+    )?;
+    let e = strict(path).await.unwrap_err();
+    let (key_path, line, column, _) = parse_error(e);
+    assert_eq!((key_path.as_str(), line, column), ("server.hots", 5, 3));
+
+    let json_path = ".test/validate/settings.json";
+    std::fs::write(
+        json_path,
+        "{\n  \"name\": \"a}p\\\"p\",\n  \"server\": { \"port\": 1, \"hots\": [2, {}] }\n}",
+    )?;
+    let e = strict(json_path).await.unwrap_err();
+    let (key_path, line, column, _) = parse_error(e);
+    assert_eq!((key_path.as_str(), line, column), ("server.hots", 3, 26));
+
+    // the validator:
+    let validated = || {
+        Config::<Settings>::default().with_validator(|s| match s.server.port {
+            0 => Err("server port must be non-zero".to_owned()),
+            _ => Ok(()),
+        })
+    };
+
+    let mut cfg = validated().load(path).await?;
+    assert_eq!(cfg.name, "app");
+    cfg.validate()?;
+
+    std::fs::write(path, "name = \"app\"\n[server]\nport = 0\n")?;
+    let e = validated().load(path).await.unwrap_err();
+    assert!(matches!(
+        *e.downcast::<Error>()?,
+        Error::ConfigInvalid { .. }
+    ));
+
+    // the validator keeps the actual data on update:
+    assert!(cfg.update().await.is_err());
+    assert_eq!(cfg.server.port, 80);
+
+    std::fs::remove_dir_all(".test/validate")?;
+    Ok(())
+}
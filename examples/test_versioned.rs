@@ -0,0 +1,39 @@
+use atoman::prelude::*;
+
+static TEXT: VersionedState<String> = VersionedState::new(3);
+
+#[tokio::main]
+async fn main() {
+    TEXT.set("a".into()).await;
+    TEXT.lock().await.push('b');
+    TEXT.blocking_map(|text| text.push('c'));
+
+    // the initial value is out of capacity:
+    let history = TEXT.history();
+    assert_eq!(history.len(), 3);
+    assert_eq!(*history[0].value, "a");
+    assert_eq!(TEXT.current().version, TEXT.version());
+
+    assert_eq!(TEXT.undo().await.as_deref().map(String::as_str), Some("ab"));
+    assert_eq!(
+        TEXT.blocking_undo().as_deref().map(String::as_str),
+        Some("a")
+    );
+    assert!(TEXT.undo().await.is_none());
+    assert_eq!(*TEXT.get().await, "a");
+
+    assert_eq!(TEXT.redo().await.as_deref().map(String::as_str), Some("ab"));
+    assert!(TEXT.can_redo());
+
+    // a new value drops the undone values:
+    TEXT.set("x".into()).await;
+    assert!(!TEXT.can_redo());
+    assert!(TEXT.blocking_redo().is_none());
+    assert_eq!(
+        TEXT.history()
+            .iter()
+            .map(|snapshot| snapshot.value.as_str())
+            .collect::<Vec<_>>(),
+        ["a", "ab", "x"]
+    );
+}
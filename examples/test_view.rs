@@ -0,0 +1,76 @@
+use atoman::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SETTINGS: State<Settings> = State::new();
+static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default, Clone)]
+pub struct Settings {
+    pub name: String,
+    pub limits: Limits,
+    pub payload: Payload,
+}
+
+/// The large data (counts the clones)
+#[derive(Default)]
+pub struct Payload;
+
+impl ::std::clone::Clone for Payload {
+    fn clone(&self) -> Self {
+        CLONES.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Limits {
+    pub max: u32,
+}
+
+/// Returns the read-only settings for library consumers
+pub fn settings() -> StateView<Settings> {
+    SETTINGS.view()
+}
+
+#[tokio::main]
+async fn main() {
+    let view = settings();
+    let mut rx = view.subscribe();
+
+    SETTINGS.map(|s| s.name = "atoman".into()).await;
+    assert_eq!(rx.changed().await.unwrap().name, "atoman");
+    assert_eq!(view.get().await.name, "atoman");
+
+    // read a field without cloning the whole struct:
+    let name = view.project(|s| &s.name);
+    assert_eq!(*name.get().await, "atoman");
+
+    // change the nested field (the whole state is cloned once per change only):
+    let limits = SETTINGS.project_mut(|s| &s.limits, |s| &mut s.limits);
+    let clones = CLONES.load(Ordering::SeqCst);
+
+    assert_eq!(limits.lock().await.max, 0);
+    assert_eq!(CLONES.load(Ordering::SeqCst), clones);
+
+    limits.lock().await.max = 10;
+    limits.blocking_map(|limits| limits.max += 5);
+    assert_eq!(CLONES.load(Ordering::SeqCst), clones + 2);
+
+    let mut guard = limits.lock().await;
+    guard.max = 20;
+    guard.abort();
+    assert_eq!(CLONES.load(Ordering::SeqCst), clones + 2);
+
+    // the other state guards wait for the projected guard:
+    let mut guard = limits.lock().await;
+    let writer = tokio::spawn(async { SETTINGS.map(|s| s.limits.max *= 2).await });
+    guard.max = 16;
+    guard.commit();
+    assert_eq!(view.dirty_get().limits.max, 16);
+    tokio::task::yield_now().await;
+    drop(guard);
+    writer.await.unwrap();
+
+    assert_eq!(*limits.get().await, Limits { max: 32 });
+    assert_eq!(view.blocking_get().limits.max, 32);
+}
@@ -0,0 +1,149 @@
+pub mod number;
+pub use number::Number;
+
+use crate::flag::Waiter;
+use crate::prelude::*;
+use std::time::Duration;
+
+/// The atomic counter
+pub type Counter = Atomic<u64>;
+
+/// The atomic number wrapper
+pub struct AtomicWrap<N: Number> {
+    value: N::Atom,
+    waiter: Waiter,
+}
+
+/// The atomic number
+pub struct Atomic<N: Number> {
+    wrap: Lazy<Arc<AtomicWrap<N>>>,
+}
+
+impl<N: Number> Atomic<N> {
+    /// Creates a new atomic number
+    pub const fn new() -> Self {
+        Self {
+            wrap: Lazy::new(|| {
+                Arc::new(AtomicWrap {
+                    value: N::new_atom(N::default()),
+                    waiter: Waiter::new(),
+                })
+            }),
+        }
+    }
+
+    /// Get actual value
+    pub fn get(&self) -> N {
+        N::load(&self.wrap.value)
+    }
+
+    /// Set a new value
+    pub fn set(&self, value: N) {
+        N::store(&self.wrap.value, value);
+        self.wrap.waiter.notify();
+    }
+
+    /// Sets a new value (returns previous value)
+    pub fn fetch_set(&self, value: N) -> N {
+        self.notify(N::swap(&self.wrap.value, value))
+    }
+
+    /// Adds to the value with wrapping on overflow (returns previous value)
+    pub fn add(&self, value: N) -> N {
+        self.notify(N::fetch_add(&self.wrap.value, value))
+    }
+
+    /// Subtracts from the value with wrapping on overflow (returns previous value)
+    pub fn sub(&self, value: N) -> N {
+        self.notify(N::fetch_sub(&self.wrap.value, value))
+    }
+
+    /// Sets the maximum of actual & new value (returns previous value)
+    pub fn fetch_max(&self, value: N) -> N {
+        self.notify(N::fetch_max(&self.wrap.value, value))
+    }
+
+    /// Sets the minimum of actual & new value (returns previous value)
+    pub fn fetch_min(&self, value: N) -> N {
+        self.notify(N::fetch_min(&self.wrap.value, value))
+    }
+
+    /// Sets a new value if the actual value is equal to 'current' (returns previous value)
+    pub fn compare_exchange(&self, current: N, new: N) -> StdResult<N, N> {
+        let result = N::compare_exchange(&self.wrap.value, current, new);
+        if result.is_ok() {
+            self.wrap.waiter.notify();
+        }
+        result
+    }
+
+    /// Wait until the value satisfies the predicate
+    pub async fn wait_until(&self, f: impl Fn(N) -> bool) {
+        self.wrap.waiter.wait(|| f(self.get())).await;
+    }
+
+    /// Wait until the value satisfies the predicate by interval (returns false on timeout)
+    pub async fn wait_until_timeout(&self, f: impl Fn(N) -> bool, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.wait_until(f))
+            .await
+            .is_ok()
+    }
+
+    /// Wait until the value satisfies the predicate (with synchronously blocking)
+    pub fn blocking_wait_until(&self, f: impl Fn(N) -> bool) {
+        self.wrap.waiter.blocking_wait(|| f(self.get()));
+    }
+
+    /// Wait until the value satisfies the predicate by interval (with synchronously blocking)
+    pub fn blocking_wait_until_timeout(&self, f: impl Fn(N) -> bool, timeout: Duration) -> bool {
+        self.wrap
+            .waiter
+            .blocking_wait_timeout(|| f(self.get()), timeout)
+    }
+
+    /// Notifies waiters & passes the previous value through
+    fn notify(&self, prev: N) -> N {
+        self.wrap.waiter.notify();
+        prev
+    }
+}
+
+impl<N: Number> ::std::default::Default for Atomic<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Number> ::std::fmt::Debug for Atomic<N> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.get())
+    }
+}
+
+impl<N: Number> ::std::fmt::Display for Atomic<N> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &self.get())
+    }
+}
+
+impl<N: Number> ::std::cmp::Eq for Atomic<N> {}
+
+impl<N: Number> ::std::cmp::PartialEq for Atomic<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<N: Number> ::std::cmp::PartialEq<N> for Atomic<N> {
+    fn eq(&self, other: &N) -> bool {
+        &self.get() == other
+    }
+}
+
+impl<N: Number> ::std::convert::From<N> for Atomic<N> {
+    fn from(value: N) -> Self {
+        let this = Self::new();
+        this.set(value);
+        this
+    }
+}
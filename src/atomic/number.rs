@@ -0,0 +1,98 @@
+use crate::prelude::*;
+use std::sync::atomic::{
+    AtomicI8, AtomicI16, AtomicI32, AtomicI64, AtomicIsize, AtomicU8, AtomicU16, AtomicU32,
+    AtomicU64, AtomicUsize,
+};
+
+/// The integer number with an atomic representation
+pub trait Number: Copy + Default + Ord + Debugging + Displaying + Send + Sync + 'static {
+    /// The atomic representation of number
+    type Atom: Send + Sync;
+
+    /// Creates a new atomic number
+    fn new_atom(value: Self) -> Self::Atom;
+
+    /// Loads the number value
+    fn load(atom: &Self::Atom) -> Self;
+
+    /// Stores a new number value
+    fn store(atom: &Self::Atom, value: Self);
+
+    /// Stores a new number value (returns previous value)
+    fn swap(atom: &Self::Atom, value: Self) -> Self;
+
+    /// Adds to the number with wrapping on overflow (returns previous value)
+    fn fetch_add(atom: &Self::Atom, value: Self) -> Self;
+
+    /// Subtracts from the number with wrapping on overflow (returns previous value)
+    fn fetch_sub(atom: &Self::Atom, value: Self) -> Self;
+
+    /// Stores the maximum of values (returns previous value)
+    fn fetch_max(atom: &Self::Atom, value: Self) -> Self;
+
+    /// Stores the minimum of values (returns previous value)
+    fn fetch_min(atom: &Self::Atom, value: Self) -> Self;
+
+    /// Stores a new number value if the current is equal to 'current' (returns previous value)
+    fn compare_exchange(atom: &Self::Atom, current: Self, new: Self) -> StdResult<Self, Self>;
+}
+
+/// Implements the Number trait for integer types
+macro_rules! impl_number {
+    ($($num:ty => $atom:ty),* $(,)?) => {
+        $(
+            impl Number for $num {
+                type Atom = $atom;
+
+                fn new_atom(value: Self) -> Self::Atom {
+                    <$atom>::new(value)
+                }
+
+                fn load(atom: &Self::Atom) -> Self {
+                    atom.load(Ordering::SeqCst)
+                }
+
+                fn store(atom: &Self::Atom, value: Self) {
+                    atom.store(value, Ordering::SeqCst)
+                }
+
+                fn swap(atom: &Self::Atom, value: Self) -> Self {
+                    atom.swap(value, Ordering::SeqCst)
+                }
+
+                fn fetch_add(atom: &Self::Atom, value: Self) -> Self {
+                    atom.fetch_add(value, Ordering::SeqCst)
+                }
+
+                fn fetch_sub(atom: &Self::Atom, value: Self) -> Self {
+                    atom.fetch_sub(value, Ordering::SeqCst)
+                }
+
+                fn fetch_max(atom: &Self::Atom, value: Self) -> Self {
+                    atom.fetch_max(value, Ordering::SeqCst)
+                }
+
+                fn fetch_min(atom: &Self::Atom, value: Self) -> Self {
+                    atom.fetch_min(value, Ordering::SeqCst)
+                }
+
+                fn compare_exchange(atom: &Self::Atom, current: Self, new: Self) -> StdResult<Self, Self> {
+                    atom.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst)
+                }
+            }
+        )*
+    };
+}
+
+impl_number! {
+    i8 => AtomicI8,
+    i16 => AtomicI16,
+    i32 => AtomicI32,
+    i64 => AtomicI64,
+    isize => AtomicIsize,
+    u8 => AtomicU8,
+    u16 => AtomicU16,
+    u32 => AtomicU32,
+    u64 => AtomicU64,
+    usize => AtomicUsize,
+}
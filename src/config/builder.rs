@@ -0,0 +1,339 @@
+use super::{Config, ConfigFormat, Format, Modify, Options, format::find_key};
+use crate::prelude::*;
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use tokio::fs;
+
+/// The config key source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Env(String),
+}
+
+impl ::std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file '{}'", path.display()),
+            Self::Env(name) => write!(f, "env '{name}'"),
+        }
+    }
+}
+
+/// The config builder layer
+enum Layer<T> {
+    Default,
+    File {
+        path: PathBuf,
+        required: bool,
+        format: Option<(Format<Value>, Format<T>)>,
+    },
+    Env {
+        prefix: String,
+        separator: String,
+    },
+}
+
+/// The layered config builder (merges sources in order, the later overrides the earlier)
+pub struct ConfigBuilder<
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+> {
+    path: Option<PathBuf>,
+    layers: Vec<Layer<T>>,
+    _data: ::std::marker::PhantomData<T>,
+}
+
+impl<T> ConfigBuilder<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Creates a new empty config builder
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            layers: vec![],
+            _data: ::std::marker::PhantomData,
+        }
+    }
+
+    /// Adds the default struct values
+    pub fn defaults(mut self) -> Self {
+        self.layers.push(Layer::Default);
+        self
+    }
+
+    /// Adds a config file (fails if the file is not exists)
+    pub fn file<P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: file_path.into(),
+            required: true,
+            format: None,
+        });
+        self
+    }
+
+    /// Adds a config file of custom format (fails if the file is not exists)
+    pub fn file_with<F: ConfigFormat, P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: file_path.into(),
+            required: true,
+            format: Some((Format::of::<F>(), Format::of::<F>())),
+        });
+        self
+    }
+
+    /// Adds a config file (skipped if the file is not exists)
+    pub fn optional_file<P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: file_path.into(),
+            required: false,
+            format: None,
+        });
+        self
+    }
+
+    /// Adds a config file of custom format (skipped if the file is not exists)
+    pub fn optional_file_with<F: ConfigFormat, P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: file_path.into(),
+            required: false,
+            format: Some((Format::of::<F>(), Format::of::<F>())),
+        });
+        self
+    }
+
+    /// Adds the environment variables (e.g. 'APP_SERVER__PORT' with prefix 'APP_' and separator '__')
+    pub fn env(mut self, prefix: impl Into<String>, separator: impl Into<String>) -> Self {
+        self.layers.push(Layer::Env {
+            prefix: prefix.into(),
+            separator: separator.into(),
+        });
+        self
+    }
+
+    /// Sets the config file path for saving (the last added file by default)
+    pub fn path<P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.path = Some(file_path.into());
+        self
+    }
+
+    /// Merges the sources & deserializes the config
+    pub async fn build(self) -> Result<Config<T>> {
+        let explicit = self.path.is_some();
+        let mut path = self.path;
+        let mut value = Value::Object(Map::new());
+        let mut sources = BTreeMap::new();
+        let mut env = BTreeMap::new();
+        let mut options = Options::default();
+
+        for layer in self.layers {
+            match layer {
+                Layer::Default => {
+                    let layer = serde_json::to_value(T::default())?;
+                    record(&layer, "", &ConfigSource::Default, &mut sources);
+                    merge(&mut value, layer);
+                }
+
+                Layer::File {
+                    path: file_path,
+                    required,
+                    format,
+                } => {
+                    if !required && !fs::try_exists(&file_path).await? {
+                        continue;
+                    }
+
+                    let contents = fs::read_to_string(&file_path).await?;
+                    let raw_format = match format {
+                        Some((raw_format, _)) => raw_format,
+                        None => Format::<Value>::by_path(&file_path)?,
+                    };
+                    let layer = (raw_format.deserialize)(&contents)
+                        .map_err(|e| Config::<T>::located(e, &file_path))?;
+                    record(
+                        &layer,
+                        "",
+                        &ConfigSource::File(file_path.clone()),
+                        &mut sources,
+                    );
+                    merge(&mut value, layer);
+
+                    // the saved file keeps its custom format:
+                    if !explicit {
+                        path = Some(file_path);
+                        options.format = format.map(|(_, format)| format);
+                        options.raw_format = format.map(|(raw_format, _)| raw_format);
+                    }
+                }
+
+                Layer::Env { prefix, separator } => {
+                    // the non-unicode variables are skipped:
+                    let mut vars: Vec<_> = std::env::vars_os()
+                        .filter_map(|(name, raw)| {
+                            Some((name.into_string().ok()?, raw.into_string().ok()?))
+                        })
+                        .filter(|(name, _)| name.starts_with(&prefix) && name.len() > prefix.len())
+                        .collect();
+                    vars.sort();
+
+                    for (name, raw) in vars {
+                        let keys: Vec<_> = name[prefix.len()..]
+                            .split(separator.as_str())
+                            .map(str::to_lowercase)
+                            .collect();
+                        let parsed = serde_json::from_str(&raw)
+                            .unwrap_or_else(|_| Value::String(raw.clone()));
+                        env.insert(keys.join("."), (name.clone(), raw));
+
+                        let layer = keys.iter().rev().fold(parsed, |value, key| {
+                            Value::Object(Map::from_iter([(key.clone(), value)]))
+                        });
+                        record(&layer, "", &ConfigSource::Env(name), &mut sources);
+                        merge(&mut value, layer);
+                    }
+                }
+            }
+        }
+
+        // the env values are kept as strings if the parsed JSON doesn't match the field type:
+        let data = loop {
+            match serde_path_to_error::deserialize::<_, T>(&value) {
+                Ok(data) => break data,
+                Err(e) => {
+                    let key = e.path().to_string();
+                    let raw = env.remove(&key).and_then(|(name, raw)| {
+                        (sources.get(&key) == Some(&ConfigSource::Env(name))).then_some(raw)
+                    });
+                    let pointer = format!("/{}", key.replace('.', "/"));
+
+                    match (raw, value.pointer_mut(&pointer)) {
+                        (Some(raw), Some(target)) if !target.is_string() => {
+                            *target = Value::String(raw)
+                        }
+                        _ => {
+                            // the error is located in the source of key:
+                            let key_path = if key == "." { String::new() } else { key };
+                            let source = source_of(&key_path, &sources);
+                            let (path, line, column) = match source {
+                                Some(ConfigSource::File(file_path)) => {
+                                    let contents =
+                                        fs::read_to_string(file_path).await.unwrap_or_default();
+                                    let (line, column) = find_key(file_path, &contents, &key_path);
+                                    (file_path.clone(), line, column)
+                                }
+                                _ => (path.unwrap_or_default(), 0, 0),
+                            };
+                            let message = match source {
+                                Some(source @ ConfigSource::Env(_)) => {
+                                    format!("{} (from {source})", e.inner())
+                                }
+                                _ => e.inner().to_string(),
+                            };
+
+                            return Err(Error::ConfigParse {
+                                path,
+                                line,
+                                column,
+                                key_path,
+                                message,
+                            }
+                            .into());
+                        }
+                    }
+                }
+            }
+        };
+
+        Ok(Config {
+            path: path.unwrap_or_default(),
+            data,
+            modify: arc!(Modify::now().into()),
+            sources,
+            options,
+        })
+    }
+}
+
+impl<T> ::std::default::Default for ConfigBuilder<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Config<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Creates a layered config builder
+    pub fn builder() -> ConfigBuilder<T> {
+        ConfigBuilder::new()
+    }
+}
+
+/// Merges the value into target (objects are merged by keys, other values are replaced)
+fn merge(target: &mut Value, value: Value) {
+    match (target, value) {
+        (Value::Object(target), Value::Object(value)) => {
+            for (key, value) in value {
+                match target.get_mut(&key) {
+                    Some(target) => merge(target, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, value) => *target = value,
+    }
+}
+
+/// Returns the source of key or of its nearest recorded parent
+fn source_of<'a>(
+    key_path: &str,
+    sources: &'a BTreeMap<String, ConfigSource>,
+) -> Option<&'a ConfigSource> {
+    let mut key = key_path;
+    loop {
+        if let Some(source) = sources.get(key) {
+            return Some(source);
+        }
+        key = &key[..key.rfind(['.', '['])?];
+    }
+}
+
+/// Records the source of each value leaf by key path (e.g. 'server.port')
+fn record(
+    value: &Value,
+    path: &str,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                record(value, &path, source, sources);
+            }
+        }
+        _ if path.is_empty() => {}
+        _ => {
+            // remove the replaced parent & nested keys:
+            sources.retain(|key, _| {
+                !(key.starts_with(path) && key[path.len()..].starts_with('.')
+                    || path.starts_with(key.as_str()) && path[key.len()..].starts_with('.'))
+            });
+            sources.insert(path.to_owned(), source.clone());
+        }
+    }
+}
@@ -0,0 +1,613 @@
+use crate::prelude::*;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The config file format
+pub trait ConfigFormat {
+    /// Deserializes the data from a raw text
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> Result<D>;
+
+    /// Deserializes the data from a raw text & rejects the unknown keys (returns an error if unsupported by format)
+    fn deserialize_strict<D: DeserializeOwned>(_contents: &str) -> Result<D> {
+        Err(Error::ConfigStrict.into())
+    }
+
+    /// Serializes the data to a raw text
+    fn serialize<S: Serialize>(data: &S) -> Result<String>;
+
+    /// Serializes the data by editing the previous raw text (keeps the comments if format supports it)
+    fn serialize_in_place<S: Serialize>(_previous: &str, data: &S) -> Result<String> {
+        Self::serialize(data)
+    }
+}
+
+/// The TOML config format
+#[cfg(feature = "toml-config")]
+pub struct TomlFormat;
+
+#[cfg(feature = "toml-config")]
+impl ConfigFormat for TomlFormat {
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Self::tracked(contents, false)
+    }
+
+    fn deserialize_strict<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Self::tracked(contents, true)
+    }
+
+    fn serialize<S: Serialize>(data: &S) -> Result<String> {
+        Ok(toml::to_string_pretty(data)?)
+    }
+
+    fn serialize_in_place<S: Serialize>(previous: &str, data: &S) -> Result<String> {
+        // the broken previous file is regenerated:
+        let Ok(mut document) = previous.parse::<toml_edit::DocumentMut>() else {
+            return Self::serialize(data);
+        };
+
+        let actual = Self::serialize(data)?.parse::<toml_edit::DocumentMut>()?;
+        edit::merge_table(document.as_table_mut(), actual.into_table());
+
+        Ok(document.to_string())
+    }
+}
+
+#[cfg(feature = "toml-config")]
+impl TomlFormat {
+    /// Deserializes the data with tracking of error location
+    fn tracked<D: DeserializeOwned>(contents: &str, strict: bool) -> Result<D> {
+        let locate = |e: &toml::de::Error| {
+            let (line, column) = e
+                .span()
+                .map_or((0, 0), |span| position(contents, span.start));
+            (line, column, e.message().to_owned())
+        };
+
+        let de = toml::Deserializer::parse(contents).map_err(|e| {
+            let (line, column, message) = locate(&e);
+            parse_error(line, column, String::new(), message)
+        })?;
+
+        let find = |key_path: &str| {
+            locate::toml(contents, key_path).map_or((0, 0), |offset| position(contents, offset))
+        };
+
+        tracked(de, strict, locate, find)
+    }
+}
+
+/// The TOML document editing (updates only the changed values with keeping decoration)
+#[cfg(feature = "toml-config")]
+mod edit {
+    use toml_edit::{Array, ArrayOfTables, InlineTable, Item, Table, Value};
+
+    /// Merges the actual table into edited (removes the missing keys & adds the new)
+    pub(super) fn merge_table(edited: &mut Table, actual: Table) {
+        edited.retain(|key, _| actual.contains_key(key));
+
+        for (key, mut item) in actual {
+            match edited.get_mut(&key) {
+                Some(edited) => merge_item(edited, item),
+                None => {
+                    reset_position(&mut item);
+                    edited.insert(&key, item);
+                }
+            }
+        }
+    }
+
+    /// Merges the actual inline table into edited
+    fn merge_inline(edited: &mut InlineTable, actual: InlineTable) {
+        edited.retain(|key, _| actual.contains_key(key));
+
+        for (key, value) in actual {
+            match edited.get_mut(&key) {
+                Some(edited) => merge_value(edited, value),
+                None => {
+                    edited.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Merges the actual array of tables into edited (by indexes)
+    fn merge_tables(edited: &mut ArrayOfTables, actual: ArrayOfTables) {
+        let len = actual.len();
+
+        for (i, mut table) in actual.into_iter().enumerate() {
+            match edited.get_mut(i) {
+                Some(edited) => merge_table(edited, table),
+                None => {
+                    table.set_position(None);
+                    edited.push(table);
+                }
+            }
+        }
+
+        while edited.len() > len {
+            edited.remove(edited.len() - 1);
+        }
+    }
+
+    /// Merges the actual array into edited (by indexes, the new values are indented as the last)
+    fn merge_array(edited: &mut Array, actual: Array) {
+        let len = actual.len();
+        let prefix = edited.iter().last().and_then(indent);
+
+        for (i, mut value) in actual.into_iter().enumerate() {
+            match edited.get_mut(i) {
+                Some(edited) => merge_value(edited, value),
+                None => match &prefix {
+                    Some(prefix) => {
+                        value.decor_mut().set_prefix(prefix.as_str());
+                        edited.push_formatted(value);
+                    }
+                    None => {
+                        value.decor_mut().clear();
+                        edited.push(value);
+                    }
+                },
+            }
+        }
+
+        // the comment after the last kept value is stored in the prefix of the next:
+        if let Some(comment) = edited.get(len).and_then(comment) {
+            let trailing = edited.trailing().as_str().unwrap_or_default();
+            let trailing = match trailing.starts_with('\n') {
+                true => format!("{comment}{trailing}"),
+                false => format!("{comment}\n{trailing}"),
+            };
+            edited.set_trailing(trailing);
+        }
+
+        while edited.len() > len {
+            edited.remove(edited.len() - 1);
+        }
+    }
+
+    /// Returns the comment lines of value prefix (without the last line break)
+    fn comment(value: &Value) -> Option<String> {
+        let prefix = value.decor().prefix()?.as_str()?;
+        let comment = &prefix[..prefix.rfind('\n')?];
+        comment.contains('#').then(|| comment.to_owned())
+    }
+
+    /// Returns the value prefix without comments (the indent of the value line)
+    fn indent(value: &Value) -> Option<String> {
+        let prefix = value.decor().prefix()?.as_str()?;
+        match prefix.rfind('\n') {
+            Some(i) => Some(format!("\n{}", &prefix[i + 1..])),
+            None => Some(prefix.to_owned()).filter(|prefix| !prefix.is_empty()),
+        }
+    }
+
+    /// Merges the actual item into edited (replaces the item of other type)
+    fn merge_item(edited: &mut Item, actual: Item) {
+        let mut actual = match (&mut *edited, actual) {
+            (Item::Table(edited), Item::Table(actual)) => return merge_table(edited, actual),
+            (Item::ArrayOfTables(edited), Item::ArrayOfTables(actual)) => {
+                return merge_tables(edited, actual);
+            }
+            (Item::Value(edited), actual) => match actual.into_value() {
+                Ok(actual) => return merge_value(edited, actual),
+                Err(actual) => actual,
+            },
+            (_, actual) => actual,
+        };
+
+        reset_position(&mut actual);
+        *edited = actual;
+    }
+
+    /// Merges the actual value into edited (keeps the comments around changed value)
+    fn merge_value(edited: &mut Value, actual: Value) {
+        match (edited, actual) {
+            (Value::InlineTable(edited), Value::InlineTable(actual)) => {
+                merge_inline(edited, actual)
+            }
+            (Value::Array(edited), Value::Array(actual)) => merge_array(edited, actual),
+            (edited, actual) if same(edited, &actual) => {}
+            (edited, mut actual) => {
+                *actual.decor_mut() = edited.decor().clone();
+                *edited = actual;
+            }
+        }
+    }
+
+    /// Returns true if the scalar values are equal (ignores the representation)
+    fn same(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::String(a), Value::String(b)) => a.value() == b.value(),
+            (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+            (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+            (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+            (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+            _ => false,
+        }
+    }
+
+    /// Resets the document positions of new tables (places them after the existing)
+    fn reset_position(item: &mut Item) {
+        match item {
+            Item::Table(table) => {
+                table.set_position(None);
+                table.iter_mut().for_each(|(_, item)| reset_position(item));
+            }
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter_mut() {
+                    table.set_position(None);
+                    table.iter_mut().for_each(|(_, item)| reset_position(item));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The JSON config format
+#[cfg(feature = "json-config")]
+pub struct JsonFormat;
+
+#[cfg(feature = "json-config")]
+impl ConfigFormat for JsonFormat {
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Self::tracked(contents, false)
+    }
+
+    fn deserialize_strict<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Self::tracked(contents, true)
+    }
+
+    fn serialize<S: Serialize>(data: &S) -> Result<String> {
+        Ok(serde_json::to_string_pretty(data)?)
+    }
+}
+
+#[cfg(feature = "json-config")]
+impl JsonFormat {
+    /// Deserializes the data with tracking of error location
+    fn tracked<D: DeserializeOwned>(contents: &str, strict: bool) -> Result<D> {
+        let locate = |e: &serde_json::Error| {
+            let message = e.to_string();
+            let message = message
+                .strip_suffix(&format!(" at line {} column {}", e.line(), e.column()))
+                .map_or(message.clone(), str::to_owned);
+            (e.line(), e.column(), message)
+        };
+
+        let find = |key_path: &str| {
+            locate::json(contents, key_path).map_or((0, 0), |offset| position(contents, offset))
+        };
+
+        let mut de = serde_json::Deserializer::from_str(contents);
+        let data = tracked(&mut de, strict, locate, find)?;
+
+        de.end().map_err(|e| {
+            let (line, column, message) = locate(&e);
+            parse_error(line, column, String::new(), message)
+        })?;
+
+        Ok(data)
+    }
+}
+
+/// The YAML config format
+#[cfg(feature = "yaml-config")]
+pub struct YamlFormat;
+
+#[cfg(feature = "yaml-config")]
+impl ConfigFormat for YamlFormat {
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Ok(serde_yaml::from_str(contents)?)
+    }
+
+    fn serialize<S: Serialize>(data: &S) -> Result<String> {
+        Ok(serde_yaml::to_string(data)?)
+    }
+}
+
+/// The RON config format
+#[cfg(feature = "ron-config")]
+pub struct RonFormat;
+
+#[cfg(feature = "ron-config")]
+impl ConfigFormat for RonFormat {
+    fn deserialize<D: DeserializeOwned>(contents: &str) -> Result<D> {
+        Ok(ron::from_str(contents)?)
+    }
+
+    fn serialize<S: Serialize>(data: &S) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(
+            data,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+}
+
+/// Deserializes the data with tracking of key path (rejects the first unknown key if strict)
+#[cfg(any(feature = "toml-config", feature = "json-config"))]
+fn tracked<'de, De, D>(
+    de: De,
+    strict: bool,
+    locate: impl Fn(&De::Error) -> (usize, usize, String),
+    find: impl Fn(&str) -> (usize, usize),
+) -> Result<D>
+where
+    De: serde::Deserializer<'de>,
+    D: serde::Deserialize<'de>,
+{
+    let mut unknown = None;
+    // the top-level migration version is allowed for data without version field:
+    let mut callback = |path: serde_ignored::Path| {
+        let path = path.to_string();
+        if path != super::VERSION_KEY {
+            unknown.get_or_insert(path);
+        }
+    };
+    let result =
+        serde_path_to_error::deserialize(serde_ignored::Deserializer::new(de, &mut callback));
+
+    match result {
+        Ok(data) => match unknown {
+            Some(key_path) if strict => {
+                let (line, column) = find(&key_path);
+                Err(parse_error(
+                    line,
+                    column,
+                    key_path,
+                    "unknown key".to_owned(),
+                ))
+            }
+            _ => Ok(data),
+        },
+        Err(e) => {
+            let key_path = match e.path().to_string() {
+                path if path == "." => String::new(),
+                path => path,
+            };
+            let (line, column, message) = locate(e.inner());
+            Err(parse_error(line, column, key_path, message))
+        }
+    }
+}
+
+/// Creates a config parsing error (the file path is set by config)
+#[cfg(any(feature = "toml-config", feature = "json-config"))]
+fn parse_error(line: usize, column: usize, key_path: String, message: String) -> DynError {
+    Error::ConfigParse {
+        path: PathBuf::new(),
+        line,
+        column,
+        key_path,
+        message,
+    }
+    .into()
+}
+
+/// The key locating in raw text (by key path, e.g. 'server.port')
+#[cfg(any(feature = "toml-config", feature = "json-config"))]
+mod locate {
+    /// Returns the key path segments (skips the optional values)
+    fn segments(key_path: &str) -> impl Iterator<Item = &str> {
+        key_path.split('.').filter(|key| *key != "?")
+    }
+
+    /// Returns the text offset of key in TOML document
+    #[cfg(feature = "toml-config")]
+    pub(super) fn toml(contents: &str, key_path: &str) -> Option<usize> {
+        let document = toml_edit::Document::parse(contents).ok()?;
+        let mut item = document.as_item();
+        let mut span = None;
+
+        for key in segments(key_path) {
+            match (item.as_table_like(), key.parse::<usize>()) {
+                (Some(table), _) => {
+                    let (key, value) = table.get_key_value(key)?;
+                    span = key.span();
+                    item = value;
+                }
+                (None, Ok(index)) => {
+                    item = item.get(index)?;
+                    span = item.span();
+                }
+                _ => return None,
+            }
+        }
+
+        span.map(|span| span.start)
+    }
+
+    /// Returns the text offset of key in JSON document
+    #[cfg(feature = "json-config")]
+    pub(super) fn json(contents: &str, key_path: &str) -> Option<usize> {
+        let bytes = contents.as_bytes();
+        let mut pos = 0;
+        let mut offset = None;
+
+        for key in segments(key_path) {
+            pos = skip_space(bytes, pos);
+
+            match bytes.get(pos)? {
+                b'{' => loop {
+                    pos = skip_space(bytes, pos + 1);
+                    let start = pos;
+                    pos = skip_string(bytes, pos)?;
+                    let name = &contents[start + 1..pos - 1];
+
+                    // skip the ':' separator:
+                    pos = skip_space(bytes, skip_space(bytes, pos) + 1);
+                    if name == key {
+                        offset = Some(start);
+                        break;
+                    }
+
+                    pos = skip_space(bytes, skip_value(bytes, pos)?);
+                    if bytes.get(pos)? != &b',' {
+                        return None;
+                    }
+                },
+                b'[' => {
+                    pos += 1;
+                    for _ in 0..key.parse::<usize>().ok()? {
+                        pos = skip_space(bytes, skip_value(bytes, skip_space(bytes, pos))?);
+                        if bytes.get(pos)? != &b',' {
+                            return None;
+                        }
+                        pos += 1;
+                    }
+
+                    pos = skip_space(bytes, pos);
+                    offset = Some(pos);
+                }
+                _ => return None,
+            }
+        }
+
+        offset
+    }
+
+    /// Skips the whitespaces
+    #[cfg(feature = "json-config")]
+    fn skip_space(bytes: &[u8], pos: usize) -> usize {
+        pos + bytes[pos.min(bytes.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count()
+    }
+
+    /// Skips the JSON string (returns the offset after closing quote)
+    #[cfg(feature = "json-config")]
+    fn skip_string(bytes: &[u8], mut pos: usize) -> Option<usize> {
+        if bytes.get(pos)? != &b'"' {
+            return None;
+        }
+        pos += 1;
+
+        loop {
+            match bytes.get(pos)? {
+                b'"' => return Some(pos + 1),
+                b'\\' => pos += 2,
+                _ => pos += 1,
+            }
+        }
+    }
+
+    /// Skips the JSON value (returns the offset after value)
+    #[cfg(feature = "json-config")]
+    fn skip_value(bytes: &[u8], mut pos: usize) -> Option<usize> {
+        let mut depth = 0;
+
+        loop {
+            match bytes.get(pos) {
+                Some(b'"') => pos = skip_string(bytes, pos)?,
+                Some(b'{' | b'[') => {
+                    depth += 1;
+                    pos += 1;
+                }
+                Some(b'}' | b']') if depth > 0 => {
+                    depth -= 1;
+                    pos += 1;
+                }
+                Some(b'}' | b']' | b',') | None => return Some(pos),
+                Some(b) if depth == 0 && b.is_ascii_whitespace() => return Some(pos),
+                Some(_) => pos += 1,
+            }
+
+            if depth == 0 && matches!(bytes.get(pos - 1), Some(b'"' | b'}' | b']')) {
+                return Some(pos);
+            }
+        }
+    }
+}
+
+/// Returns the line & column of text offset (starting from 1)
+#[cfg(any(feature = "toml-config", feature = "json-config"))]
+fn position(contents: &str, offset: usize) -> (usize, usize) {
+    let before = contents.get(..offset).unwrap_or(contents);
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Returns the line & column of key in raw text of format by file extension ((0, 0) if not found)
+#[cfg(any(feature = "toml-config", feature = "json-config"))]
+pub(super) fn find_key(path: &Path, contents: &str, key_path: &str) -> (usize, usize) {
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("TOML")
+        .to_uppercase();
+
+    let offset = match ext.as_str() {
+        #[cfg(feature = "toml-config")]
+        "TOML" => locate::toml(contents, key_path),
+
+        #[cfg(feature = "json-config")]
+        "JSON" => locate::json(contents, key_path),
+
+        _ => None,
+    };
+    offset.map_or((0, 0), |offset| position(contents, offset))
+}
+
+/// Returns the line & column of key in raw text (unsupported without TOML & JSON formats)
+#[cfg(not(any(feature = "toml-config", feature = "json-config")))]
+pub(super) fn find_key(_path: &Path, _contents: &str, _key_path: &str) -> (usize, usize) {
+    (0, 0)
+}
+
+/// The config format functions of data type
+pub(super) struct Format<D> {
+    pub(super) deserialize: fn(&str) -> Result<D>,
+    pub(super) deserialize_strict: fn(&str) -> Result<D>,
+    pub(super) serialize: fn(&D) -> Result<String>,
+    pub(super) serialize_in_place: fn(&str, &D) -> Result<String>,
+}
+
+impl<D: Serialize + DeserializeOwned> Format<D> {
+    /// Returns the functions of config format
+    pub(super) const fn of<F: ConfigFormat>() -> Self {
+        Self {
+            deserialize: F::deserialize::<D>,
+            deserialize_strict: F::deserialize_strict::<D>,
+            serialize: F::serialize::<D>,
+            serialize_in_place: F::serialize_in_place::<D>,
+        }
+    }
+
+    /// Returns the config format by file extension
+    pub(super) fn by_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("TOML")
+            .to_uppercase();
+
+        match ext.as_str() {
+            #[cfg(feature = "toml-config")]
+            "TOML" => Ok(Self::of::<TomlFormat>()),
+
+            #[cfg(feature = "json-config")]
+            "JSON" => Ok(Self::of::<JsonFormat>()),
+
+            #[cfg(feature = "yaml-config")]
+            "YAML" | "YML" => Ok(Self::of::<YamlFormat>()),
+
+            #[cfg(feature = "ron-config")]
+            "RON" => Ok(Self::of::<RonFormat>()),
+
+            _ => Err(Error::ConfigExt(ext).into()),
+        }
+    }
+}
+
+impl<D> ::std::clone::Clone for Format<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> ::std::marker::Copy for Format<D> {}
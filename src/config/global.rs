@@ -0,0 +1,185 @@
+use super::{Config, ConfigFormat, Format};
+use crate::{State, StateGuard, prelude::*};
+
+use serde::{Serialize, de::DeserializeOwned};
+use std::panic::Location;
+
+/// The global config (accessible as a static state after initialization)
+pub struct GlobalConfig<
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+> {
+    state: State<T>,
+    path: State<Option<PathBuf>>,
+    backup: bool,
+    format: Option<Format<T>>,
+}
+
+impl<T> GlobalConfig<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Creates a new global config (contains the default value until initialization)
+    pub const fn new() -> Self {
+        Self {
+            state: State::new(),
+            path: State::new(),
+            backup: false,
+            format: None,
+        }
+    }
+
+    /// Enables keeping a '.bak' copy of the previous config file on saving
+    pub const fn with_backup(mut self, backup: bool) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// Sets a custom config format (the format is chosen by file extension by default)
+    pub const fn with_format<F: ConfigFormat>(mut self) -> Self {
+        self.format = Some(Format::of::<F>());
+        self
+    }
+
+    /// Reads the config file (or creates the default) and publishes its data
+    pub async fn init<P: Into<PathBuf>>(&self, file_path: P) -> Result<()> {
+        let mut cfg = Config::<T>::default();
+        cfg.options.format = self.format;
+        let cfg = cfg.load(file_path).await?;
+
+        self.state.set(cfg.data).await;
+        self.path.set(Some(cfg.path)).await;
+
+        Ok(())
+    }
+
+    /// Returns true if the config is initialized
+    pub fn is_init(&self) -> bool {
+        self.path.dirty_get().is_some()
+    }
+
+    /// Returns the config file path
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.dirty_get_cloned()
+    }
+
+    /// Returns a config guard (changes are written to file by 'save')
+    #[track_caller]
+    pub fn lock(&self) -> impl Future<Output = ConfigGuard<T>> {
+        let lock = self.state.lock();
+        async move {
+            ConfigGuard {
+                guard: lock.await,
+                path: self.path(),
+                backup: self.backup,
+                format: self.format,
+            }
+        }
+    }
+
+    /// Writes the actual config data to file
+    pub async fn save(&self) -> Result<()> {
+        self.lock().await.save().await
+    }
+}
+
+impl<T> ::std::default::Default for GlobalConfig<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ::std::ops::Deref for GlobalConfig<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<T> ::std::fmt::Debug for GlobalConfig<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &*self.state.dirty_get())
+    }
+}
+
+/// The global config guard
+pub struct ConfigGuard<
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+> {
+    guard: StateGuard<T>,
+    path: Option<PathBuf>,
+    backup: bool,
+    format: Option<Format<T>>,
+}
+
+impl<T> ConfigGuard<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Returns the source location where the guard was acquired
+    pub fn location(&self) -> &'static Location<'static> {
+        self.guard.location()
+    }
+
+    /// Writes the config data to file & publishes the changes (discards them if writing fails)
+    pub async fn save(mut self) -> Result<()> {
+        let Some(path) = self.path.take() else {
+            self.guard.abort();
+            return Err(Error::ConfigNotInit.into());
+        };
+
+        match Config::<T>::write_file(&path, &self.guard, self.format, self.backup).await {
+            Ok(()) => {
+                self.guard.commit();
+                Ok(())
+            }
+            Err(e) => {
+                self.guard.abort();
+                Err(e)
+            }
+        }
+    }
+
+    /// Discards the changes & unlocks the config
+    pub fn abort(self) {
+        self.guard.abort();
+    }
+}
+
+impl<T> ::std::ops::Deref for ConfigGuard<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> ::std::ops::DerefMut for ConfigGuard<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> ::std::fmt::Debug for ConfigGuard<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &*self.guard)
+    }
+}
@@ -1,13 +1,54 @@
+pub mod persistent;
+pub use persistent::PersistentState;
+pub mod watcher;
+pub use watcher::ConfigWatcher;
+pub mod global;
+pub use global::{ConfigGuard, GlobalConfig};
+pub mod builder;
+pub use builder::{ConfigBuilder, ConfigSource};
+pub mod format;
+use format::Format;
+pub use format::*;
+
 use crate::{State, prelude::*};
 
 use chrono::{DateTime, Utc};
 use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
 use std::{
+    borrow::Cow,
+    collections::BTreeMap,
     path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::fs;
+use tokio::{fs, io::AsyncWriteExt};
+
+/// The config version key
+const VERSION_KEY: &str = "version";
+
+/// The temporary files counter
+static TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Logs a config warning (if the logger is enabled)
+pub(crate) fn warn(_message: impl ::std::fmt::Display) {
+    #[cfg(feature = "logger")]
+    crate::logger::warn!("{_message}");
+}
+
+/// The temporary file (removed on drop if not persisted, e.g. on failure or cancellation)
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl ::std::ops::Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
 
 /// The config modify metadata
 #[derive(Default, Debug, Clone)]
@@ -26,6 +67,51 @@ impl Modify {
     }
 }
 
+/// The config data validator
+type Validator<T> = Arc<dyn Fn(&T) -> StdResult<(), String> + Send + Sync>;
+
+/// The config raw data migration
+type Migration = Arc<dyn Fn(&mut Value) + Send + Sync>;
+
+/// The config reading & writing options
+struct Options<T> {
+    format: Option<Format<T>>,
+    raw_format: Option<Format<Value>>,
+    backup: bool,
+    strict: bool,
+    rewrite: bool,
+    validator: Option<Validator<T>>,
+    migrations: BTreeMap<u64, Migration>,
+}
+
+impl<T> ::std::default::Default for Options<T> {
+    fn default() -> Self {
+        Self {
+            format: None,
+            raw_format: None,
+            backup: false,
+            strict: false,
+            rewrite: false,
+            validator: None,
+            migrations: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> ::std::clone::Clone for Options<T> {
+    fn clone(&self) -> Self {
+        Self {
+            format: self.format,
+            raw_format: self.raw_format,
+            backup: self.backup,
+            strict: self.strict,
+            rewrite: self.rewrite,
+            validator: self.validator.clone(),
+            migrations: self.migrations.clone(),
+        }
+    }
+}
+
 /// The atomic config wrapper
 #[derive(Default, Clone)]
 pub struct Config<
@@ -34,6 +120,8 @@ pub struct Config<
     path: PathBuf,
     data: T,
     modify: Arc<State<Modify>>,
+    sources: BTreeMap<String, ConfigSource>,
+    options: Options<T>,
 }
 
 impl<T> Config<T>
@@ -42,20 +130,27 @@ where
 {
     /// Reads the config file or creates the default
     pub async fn new<P: Into<PathBuf>>(file_path: P) -> Result<Self> {
+        Self::default().load(file_path).await
+    }
+
+    /// Reads the config file of custom format or creates the default
+    pub async fn new_with<F: ConfigFormat, P: Into<PathBuf>>(file_path: P) -> Result<Self> {
+        Self::default().with_format::<F>().load(file_path).await
+    }
+
+    /// Reads the config file by the config options (or writes the actual data if file not exists)
+    pub async fn load<P: Into<PathBuf>>(mut self, file_path: P) -> Result<Self> {
         let file_path = file_path.into();
 
         // reading the config file:
-        let this = if file_path.exists() {
-            Self::read(&file_path).await?
+        if file_path.exists() {
+            Self::read_options(file_path, &self.options).await
         }
         // writing the default config file:
         else {
-            let mut this = Config::<T>::default();
-            this.write(file_path).await?;
-            this
-        };
-
-        Ok(this)
+            self.write(file_path).await?;
+            Ok(self)
+        }
     }
 
     /// Returns the config file path
@@ -63,71 +158,350 @@ where
         &self.path
     }
 
+    /// Returns the sources of config keys (filled by the layered config builder)
+    pub fn sources(&self) -> &BTreeMap<String, ConfigSource> {
+        &self.sources
+    }
+
+    /// Returns the source of config key (e.g. 'server.port')
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources.get(key)
+    }
+
+    /// Enables keeping a '.bak' copy of the previous config file on writing
+    pub fn with_backup(mut self, backup: bool) -> Self {
+        self.options.backup = backup;
+        self
+    }
+
+    /// Sets a custom config format (the format is chosen by file extension by default)
+    pub fn with_format<F: ConfigFormat>(mut self) -> Self {
+        self.options.format = Some(Format::of::<F>());
+        self.options.raw_format = Some(Format::of::<F>());
+        self
+    }
+
+    /// Enables rejecting the unknown keys on reading (supported by TOML & JSON, the other formats return an error, the top-level 'version' key is allowed)
+    pub fn with_deny_unknown(mut self, deny: bool) -> Self {
+        self.options.strict = deny;
+        self
+    }
+
+    /// Sets a validator which checks the data on reading (returns the error message on failure)
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&T) -> StdResult<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.options.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Enables rewriting the config file after reading if its serialized form differs (e.g. to add the new default keys)
+    pub fn with_rewrite(mut self, rewrite: bool) -> Self {
+        self.options.rewrite = rewrite;
+        self
+    }
+
+    /// Adds a raw data migration to version (applied to files with an older 'version' key, enables rewriting)
+    pub fn with_migration(
+        mut self,
+        version: u64,
+        migration: impl Fn(&mut Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.options.migrations.insert(version, Arc::new(migration));
+        self.options.rewrite = true;
+        self
+    }
+
+    /// Checks the actual data by validator
+    pub fn validate(&self) -> Result<()> {
+        Self::validate_data(&self.path, &self.data, &self.options)
+    }
+
+    /// Checks the data by validator of options
+    fn validate_data(path: &Path, data: &T, options: &Options<T>) -> Result<()> {
+        match &options.validator {
+            Some(validator) => validator(data).map_err(|message| {
+                Error::ConfigInvalid {
+                    path: path.to_path_buf(),
+                    message,
+                }
+                .into()
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the config format (custom or by file extension)
+    fn format(path: &Path, options: &Options<T>) -> Result<Format<T>> {
+        options.format.map_or_else(|| Format::by_path(path), Ok)
+    }
+
     /// Parses the config from a raw text
     pub fn parse<P: Into<PathBuf>>(file_path: P, contents: &str) -> Result<Self> {
-        let path = file_path.into();
-
-        let data: T = match path
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("TOML")
-            .to_uppercase()
-            .as_str()
-        {
-            #[cfg(any(feature = "toml-config"))]
-            "TOML" => toml::from_str(contents)?,
+        Self::parse_options(file_path.into(), contents, &Options::default())
+    }
 
-            #[cfg(any(feature = "json-config"))]
-            "JSON" => serde_json::from_str(contents)?,
+    /// Parses the config of custom format from a raw text
+    pub fn parse_with<F: ConfigFormat, P: Into<PathBuf>>(
+        file_path: P,
+        contents: &str,
+    ) -> Result<Self> {
+        let options = Options {
+            format: Some(Format::of::<F>()),
+            ..Default::default()
+        };
+        Self::parse_options(file_path.into(), contents, &options)
+    }
 
-            ext => return Err(Error::ConfigExt(ext.to_owned()).into()),
+    /// Parses the config from a raw text by options
+    fn parse_options(path: PathBuf, contents: &str, options: &Options<T>) -> Result<Self> {
+        let format = Self::format(&path, options)?;
+        let data = Self::deserialize_data(&path, contents, format, options)?;
+        Self::validated(path, data, options)
+    }
+
+    /// Deserializes the config data from a raw text (applies the migrations)
+    fn deserialize_data(
+        path: &Path,
+        contents: &str,
+        format: Format<T>,
+        options: &Options<T>,
+    ) -> Result<T> {
+        let deserialize = if options.strict {
+            format.deserialize_strict
+        } else {
+            format.deserialize
         };
 
+        let contents = Self::migrate(path, contents, options)?;
+        deserialize(&contents).map_err(|e| Self::located(e, path))
+    }
+
+    /// Creates the config from the validated data
+    fn validated(path: PathBuf, data: T, options: &Options<T>) -> Result<Self> {
+        Self::validate_data(&path, &data, options)?;
+
         Ok(Self {
             path,
             data,
             modify: arc!(Modify::now().into()),
+            sources: BTreeMap::new(),
+            options: options.clone(),
         })
     }
 
+    /// Applies the migrations newer than the file version to a raw text
+    fn migrate<'a>(path: &Path, contents: &'a str, options: &Options<T>) -> Result<Cow<'a, str>> {
+        let Some(&latest) = options.migrations.keys().last() else {
+            return Ok(Cow::Borrowed(contents));
+        };
+
+        let format = options
+            .raw_format
+            .map_or_else(|| Format::by_path(path), Ok)?;
+        let mut value = (format.deserialize)(contents).map_err(|e| Self::located(e, path))?;
+
+        // the file without version is older than any migration:
+        let version = value.get(VERSION_KEY).and_then(Value::as_u64).unwrap_or(0);
+        if version >= latest {
+            return Ok(Cow::Borrowed(contents));
+        }
+
+        for (&version, migration) in options.migrations.range(version + 1..) {
+            migration(&mut value);
+
+            if let Value::Object(map) = &mut value {
+                map.insert(VERSION_KEY.to_owned(), version.into());
+            }
+        }
+
+        Ok(Cow::Owned((format.serialize)(&value)?))
+    }
+
+    /// Writes the latest migration version to a raw text (the data type may have no version field)
+    fn stamp(path: &Path, contents: String, options: &Options<T>) -> Result<String> {
+        let Some(&latest) = options.migrations.keys().last() else {
+            return Ok(contents);
+        };
+
+        let format = options
+            .raw_format
+            .map_or_else(|| Format::by_path(path), Ok)?;
+        let mut value = (format.deserialize)(&contents)?;
+
+        match &mut value {
+            Value::Object(map) if map.get(VERSION_KEY).and_then(Value::as_u64) != Some(latest) => {
+                map.insert(VERSION_KEY.to_owned(), latest.into());
+                (format.serialize_in_place)(&contents, &value)
+            }
+            _ => Ok(contents),
+        }
+    }
+
+    /// Sets the file path to parsing error
+    fn located(e: DynError, path: &Path) -> DynError {
+        match e.downcast::<Error>() {
+            Ok(e) => match *e {
+                Error::ConfigParse {
+                    line,
+                    column,
+                    key_path,
+                    message,
+                    ..
+                } => Error::ConfigParse {
+                    path: path.to_path_buf(),
+                    line,
+                    column,
+                    key_path,
+                    message,
+                }
+                .into(),
+                e => e.into(),
+            },
+            Err(e) => e,
+        }
+    }
+
+    /// Parses the config from a raw text (falls back to the '.bak' file on deserializing error)
+    fn parse_or_backup(path: &Path, contents: &str, options: &Options<T>) -> Result<Self> {
+        // the invalid data doesn't fall back to the backup:
+        let format = Self::format(path, options)?;
+        let e = match Self::deserialize_data(path, contents, format, options) {
+            Ok(data) => return Self::validated(path.to_path_buf(), data, options),
+            Err(e) => e,
+        };
+
+        // the broken backup doesn't replace the original error:
+        let backup_path = Self::sibling_path(path, ".bak");
+        let Ok(backup) = std::fs::read_to_string(&backup_path) else {
+            return Err(e);
+        };
+        let this = Self::parse_options(path.to_path_buf(), &backup, options).map_err(|_| e)?;
+
+        warn(format!(
+            "Failed to parse config '{}', the backup '{}' is used",
+            path.display(),
+            backup_path.display()
+        ));
+        Ok(this)
+    }
+
+    /// Returns the file path with a suffix
+    fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(suffix);
+        path.into()
+    }
+
     /// Reads the config file
     pub async fn read<P: Into<PathBuf>>(file_path: P) -> Result<Self> {
-        let file_path = file_path.into();
+        Self::read_options(file_path.into(), &Options::default()).await
+    }
+
+    /// Reads the config file of custom format
+    pub async fn read_with<F: ConfigFormat, P: Into<PathBuf>>(file_path: P) -> Result<Self> {
+        let options = Options {
+            format: Some(Format::of::<F>()),
+            ..Default::default()
+        };
+        Self::read_options(file_path.into(), &options).await
+    }
 
+    /// Reads the config file by options
+    async fn read_options(file_path: PathBuf, options: &Options<T>) -> Result<Self> {
         // reading the config file:
+        let modified = Self::modified(&file_path).await?;
         let contents = fs::read_to_string(&file_path).await?;
-        Self::parse(file_path, &contents)
+        let mut this = Self::parse_or_backup(&file_path, &contents, options)?;
+
+        this.modify.dirty_set(Modify {
+            modified: Some(modified),
+            checked: Some(Instant::now()),
+        });
+
+        // rewriting the outdated config file:
+        if options.rewrite
+            && Self::stamp(
+                &file_path,
+                (Self::format(&file_path, options)?.serialize_in_place)(&contents, &this.data)?,
+                options,
+            )? != contents
+        {
+            this.save().await?;
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the config file modification time
+    async fn modified(path: &Path) -> Result<DateTime<Utc>> {
+        Ok(fs::metadata(path).await?.modified()?.into())
     }
 
     /// Saves the config to custom file path
     pub async fn write<P: Into<PathBuf>>(&mut self, file_path: P) -> Result<()> {
         self.path = file_path.into();
+        let format = Self::format(&self.path, &self.options)?;
+        let contents = Self::render(&self.path, &self.data, format).await?;
+        let contents = Self::stamp(&self.path, contents, &self.options)?;
+        Self::write_contents(&self.path, &contents, self.options.backup).await?;
 
-        // serialize to .toml string:
-        let contents = match self
-            .path
-            .extension()
-            .map(|s| s.to_str().unwrap_or("TOML"))
-            .unwrap_or("TOML")
-            .to_uppercase()
-            .as_ref()
-        {
-            #[cfg(any(feature = "toml-config"))]
-            "TOML" => toml::to_string_pretty(&self.data)?,
+        // remember own changes to not reload them:
+        let modified = Self::modified(&self.path).await?;
+        self.modify.map(|m| m.modified = Some(modified)).await;
 
-            #[cfg(any(feature = "json-config"))]
-            "JSON" => serde_json::to_string_pretty(&self.data)?,
+        Ok(())
+    }
 
-            ext => return Err(Error::ConfigExt(ext.to_owned()).into()),
-        };
+    /// Writes the config data to file (by custom format or file extension)
+    async fn write_file(
+        path: &Path,
+        data: &T,
+        format: Option<Format<T>>,
+        backup: bool,
+    ) -> Result<()> {
+        let format = format.map_or_else(|| Format::by_path(path), Ok)?;
+        let contents = Self::render(path, data, format).await?;
+        Self::write_contents(path, &contents, backup).await
+    }
+
+    /// Serializes the data by editing the existing config file (or regenerates if file not exists)
+    async fn render(path: &Path, data: &T, format: Format<T>) -> Result<String> {
+        match fs::read_to_string(path).await {
+            Ok(previous) => (format.serialize_in_place)(&previous, data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (format.serialize)(data),
+            Err(e) => Err(e.into()),
+        }
+    }
 
+    /// Writes the contents to file atomically (keeps the previous file as '.bak' if backup)
+    async fn write_contents(path: &Path, contents: &str, backup: bool) -> Result<()> {
         // create dir:
-        if let Some(parent_dir) = self.path.parent() {
+        if let Some(parent_dir) = path.parent() {
             fs::create_dir_all(parent_dir).await?;
         }
 
-        // write file:
-        fs::write(&self.path, contents).await?;
+        // write temporary file:
+        let id = TEMP_ID.fetch_add(1, Ordering::Relaxed);
+        let mut temp = TempFile {
+            path: Self::sibling_path(path, &format!(".{id}.tmp")),
+            persisted: false,
+        };
+
+        let mut file = fs::File::create(&temp.path).await?;
+        file.write_all(contents.as_bytes()).await?;
+        file.sync_all().await?;
+        drop(file);
+
+        // copy the previous file:
+        if backup && fs::try_exists(path).await? {
+            fs::copy(path, Self::sibling_path(path, ".bak")).await?;
+        }
+
+        // replace file:
+        fs::rename(&temp.path, path).await?;
+        temp.persisted = true;
 
         Ok(())
     }
@@ -159,8 +533,7 @@ where
         }
 
         // checking the actual file metadata:
-        let meta = fs::metadata(&self.path).await?;
-        let modified: DateTime<Utc> = meta.modified()?.into();
+        let modified = Self::modified(&self.path).await?;
 
         if let Some(&last_modified) = self.modify.dirty_get().modified.as_ref() {
             if modified <= last_modified {
@@ -176,11 +549,25 @@ where
 
     /// Updates the struct data from config file (returns true if updated)
     pub async fn update(&mut self) -> Result<bool> {
+        // skip the unmodified file:
+        let modified = Self::modified(&self.path).await?;
+        if self.modify.dirty_get().modified == Some(modified) {
+            return Ok(false);
+        }
+
         // read the actual file contents:
-        let cfg = Self::read(&self.path).await?;
-        *self = cfg;
+        let cfg = Self::read_options(self.path.clone(), &self.options).await?;
+        let serialize = Self::format(&self.path, &self.options)?.serialize;
+        let changed = serialize(&cfg.data)? != serialize(&self.data)?;
+        self.data = cfg.data;
+        self.modify = cfg.modify;
 
-        Ok(true)
+        Ok(changed)
+    }
+
+    /// Spawns a watcher task which reloads the config file on changes (stops on handle drop)
+    pub fn watch(&self, interval: Duration) -> ConfigWatcher<T> {
+        ConfigWatcher::spawn(self.clone(), interval)
     }
 }
 
@@ -261,6 +648,8 @@ where
             path: Default::default(),
             data: value,
             modify: arc!(Modify::now().into()),
+            sources: BTreeMap::new(),
+            options: Options::default(),
         }
     }
 }
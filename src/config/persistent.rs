@@ -0,0 +1,210 @@
+use super::{Config, ConfigFormat, Format, Options, warn};
+use crate::{State, prelude::*};
+
+use once_cell::sync::OnceCell;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{borrow::Cow, time::Duration};
+use tokio::{runtime::Handle, task::JoinHandle};
+
+/// The default delay between state change and file writing
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The persisted state data
+struct Persisted<T: Clone + Send + Sync> {
+    state: Arc<State<T>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+/// The atomic state persisted to a config file
+pub struct PersistentState<
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+> {
+    path: Cow<'static, str>,
+    debounce: Duration,
+    backup: bool,
+    format: Option<Format<T>>,
+    inner: OnceCell<Persisted<T>>,
+}
+
+impl<T> PersistentState<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Creates a new persistent state (the file is read on first access)
+    pub const fn new(file_path: &'static str) -> Self {
+        Self {
+            path: Cow::Borrowed(file_path),
+            debounce: DEBOUNCE,
+            backup: false,
+            format: None,
+            inner: OnceCell::new(),
+        }
+    }
+
+    /// Creates a new persistent state with a runtime file path
+    pub fn open<P: Into<String>>(file_path: P) -> Self {
+        Self {
+            path: Cow::Owned(file_path.into()),
+            debounce: DEBOUNCE,
+            backup: false,
+            format: None,
+            inner: OnceCell::new(),
+        }
+    }
+
+    /// Sets the delay between state change and file writing
+    pub const fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Enables keeping a '.bak' copy of the previous config file on writing
+    pub const fn with_backup(mut self, backup: bool) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// Sets a custom config format (the format is chosen by file extension by default)
+    pub const fn with_format<F: ConfigFormat>(mut self) -> Self {
+        self.format = Some(Format::of::<F>());
+        self
+    }
+
+    /// Returns the config file path
+    pub fn path(&self) -> &Path {
+        Path::new(self.path.as_ref())
+    }
+
+    /// Returns the config reading options
+    fn options(&self) -> Options<T> {
+        Options {
+            format: self.format,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the config file (or creates the default) if the state is not initialized yet
+    pub async fn init(&self) -> Result<()> {
+        if self.inner.get().is_none() {
+            let data = if self.path().exists() {
+                Config::<T>::read_options(self.path().to_path_buf(), &self.options())
+                    .await?
+                    .data
+            } else {
+                T::default()
+            };
+            let _ = self.inner.set(self.persist(data));
+        }
+
+        Ok(())
+    }
+
+    /// Writes the actual state value to file immediately
+    pub async fn flush(&self) -> Result<()> {
+        let data = self.get().await;
+        Config::<T>::write_file(self.path(), &data, self.format, self.backup).await
+    }
+
+    /// Returns the persisted state (restores it from file on first access, uses the default on failure)
+    fn persisted(&self) -> &Persisted<T> {
+        self.inner.get_or_init(|| {
+            let data = if self.path().exists() {
+                std::fs::read_to_string(self.path())
+                    .map_err(DynError::from)
+                    .and_then(|contents| {
+                        Config::<T>::parse_or_backup(self.path(), &contents, &self.options())
+                    })
+                    .map(|cfg| cfg.data)
+                    .unwrap_or_else(|e| {
+                        warn(format!(
+                            "Failed to restore the persistent state '{}', using the default: {e}",
+                            self.path().display()
+                        ));
+                        T::default()
+                    })
+            } else {
+                T::default()
+            };
+            self.persist(data)
+        })
+    }
+
+    /// Creates the state & spawns the debounced file writer (if the tokio runtime exists)
+    fn persist(&self, data: T) -> Persisted<T> {
+        let state = Arc::new(State::from(data));
+        let path = self.path().to_path_buf();
+        let debounce = self.debounce;
+        let backup = self.backup;
+        let format = self.format;
+        let mut rx = state.subscribe();
+        let weak = Arc::downgrade(&state);
+
+        let Ok(runtime) = Handle::try_current() else {
+            warn(format!(
+                "The persistent state '{}' is accessed outside of tokio runtime, changes are written by 'flush' only",
+                path.display()
+            ));
+            return Persisted {
+                state,
+                writer: None,
+            };
+        };
+
+        let writer = runtime.spawn(async move {
+            // write the default config file:
+            if !path.exists()
+                && let Some(state) = weak.upgrade()
+                && let Err(e) =
+                    Config::<T>::write_file(&path, &state.dirty_get(), format, backup).await
+            {
+                warn(format!("Failed to write config '{}': {e}", path.display()));
+            }
+
+            while rx.changed().await.is_some() {
+                tokio::time::sleep(debounce).await;
+
+                // take the latest value after the debounce delay:
+                let data = rx.borrow_and_update();
+                if let Err(e) = Config::<T>::write_file(&path, &data, format, backup).await {
+                    warn(format!("Failed to write config '{}': {e}", path.display()));
+                }
+            }
+        });
+
+        Persisted {
+            state,
+            writer: Some(writer),
+        }
+    }
+}
+
+impl<T> ::std::ops::Deref for PersistentState<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.persisted().state
+    }
+}
+
+impl<T> ::std::ops::Drop for PersistentState<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(writer) = self.inner.get().and_then(|inner| inner.writer.as_ref()) {
+            writer.abort();
+        }
+    }
+}
+
+impl<T> ::std::fmt::Debug for PersistentState<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &**self)
+    }
+}
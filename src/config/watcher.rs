@@ -0,0 +1,109 @@
+use super::Config;
+use crate::{State, prelude::*};
+
+use serde::{Serialize, de::DeserializeOwned};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// The config change callback
+type OnChange<T> = Arc<dyn Fn(&T, &T) + Send + Sync>;
+
+/// The config file watcher (stops watching on drop)
+pub struct ConfigWatcher<
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+> {
+    state: Arc<State<T>>,
+    callbacks: Arc<Mutex<Vec<OnChange<T>>>>,
+    handle: JoinHandle<()>,
+}
+
+impl<T> ConfigWatcher<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Spawns a new config file watcher
+    pub(super) fn spawn(mut config: Config<T>, interval: Duration) -> Self {
+        let state = Arc::new(State::from(config.data.clone()));
+        let callbacks = Arc::new(Mutex::new(Vec::<OnChange<T>>::new()));
+
+        let handle = tokio::spawn({
+            let state = state.clone();
+            let callbacks = callbacks.clone();
+
+            async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+
+                    match config.update().await {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(e) => {
+                            super::warn(format!(
+                                "Failed to reload config '{}': {e}",
+                                config.path().display()
+                            ));
+                            continue;
+                        }
+                    }
+
+                    // read the old & new values under the lock to not mix them with other writers:
+                    let mut guard = state.lock().await;
+                    let old = state.dirty_get();
+                    *guard = config.data.clone();
+                    guard.commit();
+                    let new = state.dirty_get();
+                    drop(guard);
+
+                    // the callbacks could register other callbacks:
+                    let actual = callbacks.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                    for callback in actual {
+                        callback(&old, &new);
+                    }
+                }
+            }
+        });
+
+        Self {
+            state,
+            callbacks,
+            handle,
+        }
+    }
+
+    /// Registers a callback called with the old and new values on each config change
+    pub fn on_change(&self, callback: impl Fn(&T, &T) + Send + Sync + 'static) -> &Self {
+        self.callbacks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(callback));
+        self
+    }
+
+    /// Returns the state with the actual config data
+    pub fn state(&self) -> Arc<State<T>> {
+        self.state.clone()
+    }
+
+    /// Stops the config file watcher
+    pub fn stop(self) {}
+}
+
+impl<T> ::std::ops::Deref for ConfigWatcher<T>
+where
+    T: Clone + Default + Debugging + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<T> ::std::ops::Drop for ConfigWatcher<T>
+where
+    T: Default + Debugging + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
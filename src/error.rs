@@ -11,10 +11,60 @@ pub enum Error {
     #[display = "Logger initialize error: {0}"]
     LoggerInit(log::SetLoggerError),
 
-    #[cfg(any(feature = "json-config", feature = "toml-config"))]
+    #[cfg(any(
+        feature = "json-config",
+        feature = "toml-config",
+        feature = "yaml-config",
+        feature = "ron-config"
+    ))]
     #[display = "Unsupported config extension '.{0}'."]
     ConfigExt(String),
 
+    #[cfg(any(
+        feature = "json-config",
+        feature = "toml-config",
+        feature = "yaml-config",
+        feature = "ron-config"
+    ))]
+    #[display = "Config is not initialized"]
+    ConfigNotInit,
+
+    #[cfg(any(
+        feature = "json-config",
+        feature = "toml-config",
+        feature = "yaml-config",
+        feature = "ron-config"
+    ))]
+    #[display = "Failed to parse config {path:?} at {line}:{column} (key '{key_path}'): {message}"]
+    ConfigParse {
+        path: std::path::PathBuf,
+        line: usize,
+        column: usize,
+        key_path: String,
+        message: String,
+    },
+
+    #[cfg(any(
+        feature = "json-config",
+        feature = "toml-config",
+        feature = "yaml-config",
+        feature = "ron-config"
+    ))]
+    #[display = "Invalid config {path:?}: {message}"]
+    ConfigInvalid {
+        path: std::path::PathBuf,
+        message: String,
+    },
+
+    #[cfg(any(
+        feature = "json-config",
+        feature = "toml-config",
+        feature = "yaml-config",
+        feature = "ron-config"
+    ))]
+    #[display = "Config format doesn't support rejecting the unknown keys"]
+    ConfigStrict,
+
     #[cfg(feature = "trace")]
     #[display = "Failed to open file: {0}"]
     OpenFile(std::io::Error),
@@ -30,4 +80,8 @@ pub enum Error {
     #[cfg(feature = "stream")]
     #[display = "Stream channel is already closed"]
     StreamClosed,
+
+    #[cfg(feature = "registry")]
+    #[display = "Unknown registry entry '{0}'."]
+    UnknownEntry(String),
 }
@@ -1,11 +1,14 @@
+pub(crate) mod waiter;
+pub(crate) use waiter::Waiter;
+
 use crate::prelude::*;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 /// The atomic flag wrapper
 #[derive(Clone)]
 pub struct FlagWrap {
     state: Arc<AtomicBool>,
-    notify: Arc<Notify>,
+    waiter: Arc<Waiter>,
 }
 
 /// The atomic flag
@@ -20,7 +23,7 @@ impl Flag {
             wrap: Lazy::new(|| {
                 Arc::new(FlagWrap {
                     state: Arc::new(AtomicBool::new(false)),
-                    notify: Arc::new(Notify::new()),
+                    waiter: Arc::new(Waiter::new()),
                 })
             }),
         }
@@ -44,46 +47,85 @@ impl Flag {
     /// Set a new state
     pub fn set(&self, value: bool) {
         self.wrap.state.store(value, Ordering::SeqCst);
-        self.wrap.notify.notify_waiters();
+        self.wrap.waiter.notify();
+    }
+
+    /// Sets a new state if the current state is equal to 'current' (returns previous state)
+    pub fn compare_exchange(&self, current: bool, new: bool) -> StdResult<bool, bool> {
+        let result =
+            self.wrap
+                .state
+                .compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst);
+
+        if result.is_ok() && current != new {
+            self.wrap.waiter.notify();
+        }
+        result
+    }
+
+    /// Sets a new state (returns previous state)
+    pub fn fetch_set(&self, value: bool) -> bool {
+        let prev = self.wrap.state.swap(value, Ordering::SeqCst);
+        self.wrap.waiter.notify();
+        prev
+    }
+
+    /// Inverts the state (returns previous state)
+    pub fn toggle(&self) -> bool {
+        let prev = self.wrap.state.fetch_xor(true, Ordering::SeqCst);
+        self.wrap.waiter.notify();
+        prev
+    }
+
+    /// Tries to switch state from 'false' to 'true' (returns true if succeeded)
+    pub fn try_acquire(&self) -> bool {
+        self.compare_exchange(false, true).is_ok()
     }
 
     /// Wait for state change
     pub async fn wait(&self, value: bool) {
-        while self.get() != value {
-            self.wrap.notify.notified().await;
+        self.wrap.waiter.wait(|| self.get() == value).await;
+    }
+
+    /// Wait for state change by interval (returns false on timeout)
+    pub async fn wait_timeout(&self, value: bool, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.wait(value))
+            .await
+            .is_ok()
+    }
+
+    /// Wait for state change or cancellation future (returns false on cancel)
+    pub async fn wait_or_cancel(&self, value: bool, cancel: impl Future) -> bool {
+        tokio::select! {
+            _ = self.wait(value) => true,
+            _ = cancel => false,
         }
     }
 
     /// Wait for state change (with synchronously blocking)
     pub fn blocking_wait(&self, value: bool) {
-        while self.get() != value {
-            std::thread::yield_now();
-        }
+        self.wrap.waiter.blocking_wait(|| self.get() == value);
     }
 
     /// Wait for state change by interval (with synchronously blocking)
     pub fn blocking_wait_timeout(&self, value: bool, timeout: Duration) -> bool {
-        let deadline = Instant::now() + timeout;
-
-        while self.get() != value {
-            if Instant::now() > deadline {
-                return false;
-            }
-            std::thread::yield_now();
-        }
-        true
+        self.wrap
+            .waiter
+            .blocking_wait_timeout(|| self.get() == value, timeout)
     }
 
     /// Wait & swap flag
     pub async fn swap(&self, value: bool) {
-        self.wait(!value).await;
-        self.set(value);
+        while self.compare_exchange(!value, value).is_err() {
+            self.wait(!value).await;
+        }
     }
 
     /// Wait & swap flag (with synchronously blocking)
     pub fn blocking_swap(&self, value: bool) {
-        self.blocking_wait(!value);
-        self.set(value);
+        while self.compare_exchange(!value, value).is_err() {
+            self.blocking_wait(!value);
+        }
     }
 }
 
@@ -0,0 +1,74 @@
+use crate::prelude::*;
+use std::sync::PoisonError;
+use std::time::{Duration, Instant};
+
+/// The async & blocking waiters notifier
+#[derive(Default)]
+pub(crate) struct Waiter {
+    notify: Notify,
+    mutex: Mutex<()>,
+    condvar: Condvar,
+}
+
+impl Waiter {
+    /// Creates a new waiter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wakes up all async tasks & parked threads
+    pub fn notify(&self) {
+        self.notify.notify_waiters();
+
+        // taking the mutex prevents a lost wake-up between condition check and parking:
+        let _lock = self.mutex.lock().unwrap_or_else(PoisonError::into_inner);
+        self.condvar.notify_all();
+    }
+
+    /// Waits until the condition is true
+    pub async fn wait(&self, cond: impl Fn() -> bool) {
+        loop {
+            // subscribe before checking to avoid missing the notification:
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if cond() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Waits until the condition is true (with parking the thread)
+    pub fn blocking_wait(&self, cond: impl Fn() -> bool) {
+        let mut lock = self.mutex.lock().unwrap_or_else(PoisonError::into_inner);
+
+        while !cond() {
+            lock = self
+                .condvar
+                .wait(lock)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Waits until the condition is true by interval (returns false on timeout)
+    pub fn blocking_wait_timeout(&self, cond: impl Fn() -> bool, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut lock = self.mutex.lock().unwrap_or_else(PoisonError::into_inner);
+
+        while !cond() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+
+            lock = self
+                .condvar
+                .wait_timeout(lock, left)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        true
+    }
+}
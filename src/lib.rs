@@ -5,15 +5,32 @@ pub mod prelude;
 
 pub mod flag;
 pub use flag::Flag;
+pub mod atomic;
+pub use atomic::{Atomic, Counter, Number};
 pub mod state;
-pub use state::{State, StateGuard};
+#[cfg(feature = "diagnostics")]
+pub use state::Diagnostics;
+pub use state::{
+    DeriveSource, DerivedState, Overflow, Projected, ProjectedGuard, Projection, ProjectionMut,
+    Snapshot, State, StateGuard, StateMap, StateQueue, StateReceiver, StateView, VersionedState,
+};
 
 pub use arc_swap::{self, ArcSwap, ArcSwapAny};
 pub use once_cell::{self, sync::Lazy};
 
-#[cfg(any(feature = "json-config", feature = "toml-config"))]
+#[cfg(any(
+    feature = "json-config",
+    feature = "toml-config",
+    feature = "yaml-config",
+    feature = "ron-config"
+))]
 pub mod config;
-#[cfg(any(feature = "json-config", feature = "toml-config"))]
+#[cfg(any(
+    feature = "json-config",
+    feature = "toml-config",
+    feature = "yaml-config",
+    feature = "ron-config"
+))]
 pub use config::*;
 
 #[cfg(feature = "logger")]
@@ -36,6 +53,11 @@ pub mod file;
 #[cfg(feature = "file")]
 pub use file::*;
 
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "registry")]
+pub use registry::*;
+
 /// Initializes a static variable by 'once_cell::Lazy'
 #[macro_export]
 macro_rules! lazy {
@@ -43,3 +65,29 @@ macro_rules! lazy {
         $crate::Lazy::new(|| $e)
     };
 }
+
+/// Declares a static variable by 'lazy!' and registers it in 'Registry' on first access
+///
+/// The optional leading 'fn name;' generates a function which registers all the statics eagerly.
+#[cfg(feature = "registry")]
+#[macro_export]
+macro_rules! register {
+    ($fn_vis:vis fn $init:ident; $($(#[$attr:meta])* $vis:vis static $name:ident : $ty:ty = $e:expr;)+) => {
+        /// Registers the statics in 'Registry' (initializes them)
+        $fn_vis fn $init() {
+            $( $crate::Lazy::force(&$name); )+
+        }
+
+        $crate::register! { $($(#[$attr])* $vis static $name: $ty = $e;)+ }
+    };
+
+    ($($(#[$attr:meta])* $vis:vis static $name:ident : $ty:ty = $e:expr;)+) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: $crate::Lazy<$ty> = $crate::lazy!({
+                $crate::registry::Registry::register(stringify!($name), &$name);
+                $e
+            });
+        )+
+    };
+}
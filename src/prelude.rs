@@ -5,8 +5,8 @@ pub(crate) use std::fmt::Debug as Debugging;
 pub(crate) use std::fmt::Display as Displaying;
 pub(crate) use std::path::{Path, PathBuf};
 pub(crate) use std::sync::{
-    Arc, Mutex, MutexGuard,
-    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex, MutexGuard,
+    atomic::{AtomicBool, AtomicU64, Ordering},
 };
 pub(crate) use tokio::sync::Notify;
 
@@ -0,0 +1,178 @@
+use crate::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, pin::Pin};
+
+/// The boxed registry future
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The registered entries
+static ENTRIES: Lazy<Mutex<BTreeMap<String, &'static dyn Registrable>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// The value which can be registered by name
+pub trait Registrable: Send + Sync {
+    /// Returns the actual value as JSON
+    fn to_json(&self) -> Result<Value>;
+
+    /// Sets a new value from JSON
+    fn set_json(&self, value: Value) -> BoxFuture<'_, Result<()>>;
+
+    /// Sets a new value from JSON (with synchronously blocking)
+    fn blocking_set_json(&self, value: Value) -> Result<()>;
+}
+
+/// The global registry of named statics
+pub struct Registry;
+
+impl Registry {
+    /// Registers an entry by name (replaces the existing entry)
+    pub fn register(name: impl Into<String>, entry: &'static dyn Registrable) {
+        Self::lock_entries().insert(name.into(), entry);
+    }
+
+    /// Unregisters an entry by name
+    pub fn unregister(name: &str) -> bool {
+        Self::lock_entries().remove(name).is_some()
+    }
+
+    /// Checks for the entry exists
+    pub fn contains(name: &str) -> bool {
+        Self::lock_entries().contains_key(name)
+    }
+
+    /// Returns the registered names
+    pub fn names() -> Vec<String> {
+        Self::lock_entries().keys().cloned().collect()
+    }
+
+    /// Returns the entry value as JSON
+    pub fn get_json(name: &str) -> Result<Value> {
+        Self::entry(name)?.to_json()
+    }
+
+    /// Returns all the entry values as JSON object
+    pub fn dump() -> Result<Value> {
+        let entries = Self::lock_entries().clone();
+        let mut map = Map::new();
+
+        for (name, entry) in entries {
+            map.insert(name, entry.to_json()?);
+        }
+
+        Ok(Value::Object(map))
+    }
+
+    /// Sets the entry value from string (JSON or plain text if JSON doesn't match the entry type)
+    pub async fn set(name: &str, value: &str) -> Result<()> {
+        let entry = Self::entry(name)?;
+
+        match Self::parse(value) {
+            Value::String(text) => entry.set_json(Value::String(text)).await,
+            parsed => match entry.set_json(parsed).await {
+                Err(e) => entry
+                    .set_json(Value::String(value.to_owned()))
+                    .await
+                    .map_err(|_| e),
+                ok => ok,
+            },
+        }
+    }
+
+    /// Sets the entry value from string (with synchronously blocking)
+    pub fn blocking_set(name: &str, value: &str) -> Result<()> {
+        let entry = Self::entry(name)?;
+
+        match Self::parse(value) {
+            Value::String(text) => entry.blocking_set_json(Value::String(text)),
+            parsed => entry.blocking_set_json(parsed).or_else(|e| {
+                entry
+                    .blocking_set_json(Value::String(value.to_owned()))
+                    .map_err(|_| e)
+            }),
+        }
+    }
+
+    /// Returns the entry by name
+    fn entry(name: &str) -> Result<&'static dyn Registrable> {
+        Self::lock_entries()
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::UnknownEntry(name.to_owned()).into())
+    }
+
+    /// Parses a string as JSON (a plain text becomes JSON string)
+    fn parse(value: &str) -> Value {
+        serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_owned()))
+    }
+
+    /// Locks the entries map
+    fn lock_entries() -> MutexGuard<'static, BTreeMap<String, &'static dyn Registrable>> {
+        ENTRIES.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Registrable for Flag {
+    fn to_json(&self) -> Result<Value> {
+        Ok(Value::Bool(self.get()))
+    }
+
+    fn set_json(&self, value: Value) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.blocking_set_json(value) })
+    }
+
+    fn blocking_set_json(&self, value: Value) -> Result<()> {
+        self.set(serde_json::from_value(value)?);
+        Ok(())
+    }
+}
+
+impl<N: Number + Serialize + DeserializeOwned> Registrable for Atomic<N> {
+    fn to_json(&self) -> Result<Value> {
+        Ok(serde_json::to_value(self.get())?)
+    }
+
+    fn set_json(&self, value: Value) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.blocking_set_json(value) })
+    }
+
+    fn blocking_set_json(&self, value: Value) -> Result<()> {
+        self.set(serde_json::from_value(value)?);
+        Ok(())
+    }
+}
+
+impl<T> Registrable for State<T>
+where
+    T: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    fn to_json(&self) -> Result<Value> {
+        Ok(serde_json::to_value(&*self.dirty_get())?)
+    }
+
+    fn set_json(&self, value: Value) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.set(serde_json::from_value(value)?).await;
+            Ok(())
+        })
+    }
+
+    fn blocking_set_json(&self, value: Value) -> Result<()> {
+        self.blocking_set(serde_json::from_value(value)?);
+        Ok(())
+    }
+}
+
+impl<T: Registrable> Registrable for Lazy<T> {
+    fn to_json(&self) -> Result<Value> {
+        (**self).to_json()
+    }
+
+    fn set_json(&self, value: Value) -> BoxFuture<'_, Result<()>> {
+        (**self).set_json(value)
+    }
+
+    fn blocking_set_json(&self, value: Value) -> Result<()> {
+        (**self).blocking_set_json(value)
+    }
+}
@@ -0,0 +1,241 @@
+use super::{State, StateView, StateWrap, VersionedState};
+use crate::prelude::*;
+use std::any::Any;
+
+/// The computing function of derived state
+type Compute<T> = Arc<dyn Fn() -> Option<T> + Send + Sync>;
+
+/// The source of derived state
+pub trait DeriveSource<T: Clone + Send + Sync> {
+    /// Returns the source state wrapper
+    fn source(&self) -> &Arc<StateWrap<T>>;
+}
+
+impl<T: Clone + Send + Sync> DeriveSource<T> for State<T> {
+    fn source(&self) -> &Arc<StateWrap<T>> {
+        self.wrap()
+    }
+}
+
+impl<T: Clone + Send + Sync> DeriveSource<T> for VersionedState<T> {
+    fn source(&self) -> &Arc<StateWrap<T>> {
+        self.wrap()
+    }
+}
+
+impl<T: Clone + Send + Sync> DeriveSource<T> for StateView<T> {
+    fn source(&self) -> &Arc<StateWrap<T>> {
+        &self.wrap
+    }
+}
+
+impl<T: Clone + Send + Sync> DeriveSource<T> for DerivedState<T> {
+    fn source(&self) -> &Arc<StateWrap<T>> {
+        &self.view.wrap
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> State<T> {
+    /// Creates a read-only state which recomputes on each source change
+    ///
+    /// Warning: the derived states must not form a cycle.
+    pub fn derive<S, F>(source: &impl DeriveSource<S>, f: F) -> DerivedState<T>
+    where
+        S: Clone + Send + Sync + 'static,
+        F: Fn(&S) -> T + Send + Sync + 'static,
+    {
+        let source = source.source();
+        let version = source.version();
+        let weak = Arc::downgrade(source);
+
+        DerivedState::new(Arc::new(move || Some(f(&weak.upgrade()?.swap.load_full()))))
+            .listen(source, version)
+    }
+
+    /// Creates a read-only state which recomputes on each change of any of two sources
+    pub fn derive2<A, B, F>(
+        a: &impl DeriveSource<A>,
+        b: &impl DeriveSource<B>,
+        f: F,
+    ) -> DerivedState<T>
+    where
+        A: Clone + Send + Sync + 'static,
+        B: Clone + Send + Sync + 'static,
+        F: Fn(&A, &B) -> T + Send + Sync + 'static,
+    {
+        let (a, b) = (a.source(), b.source());
+        let (version_a, version_b) = (a.version(), b.version());
+        let (weak_a, weak_b) = (Arc::downgrade(a), Arc::downgrade(b));
+
+        DerivedState::new(Arc::new(move || {
+            Some(f(
+                &weak_a.upgrade()?.swap.load_full(),
+                &weak_b.upgrade()?.swap.load_full(),
+            ))
+        }))
+        .listen(a, version_a)
+        .listen(b, version_b)
+    }
+
+    /// Creates a read-only state which recomputes on each change of any of three sources
+    pub fn derive3<A, B, C, F>(
+        a: &impl DeriveSource<A>,
+        b: &impl DeriveSource<B>,
+        c: &impl DeriveSource<C>,
+        f: F,
+    ) -> DerivedState<T>
+    where
+        A: Clone + Send + Sync + 'static,
+        B: Clone + Send + Sync + 'static,
+        C: Clone + Send + Sync + 'static,
+        F: Fn(&A, &B, &C) -> T + Send + Sync + 'static,
+    {
+        let (a, b, c) = (a.source(), b.source(), c.source());
+        let (version_a, version_b, version_c) = (a.version(), b.version(), c.version());
+        let (weak_a, weak_b, weak_c) = (Arc::downgrade(a), Arc::downgrade(b), Arc::downgrade(c));
+
+        DerivedState::new(Arc::new(move || {
+            Some(f(
+                &weak_a.upgrade()?.swap.load_full(),
+                &weak_b.upgrade()?.swap.load_full(),
+                &weak_c.upgrade()?.swap.load_full(),
+            ))
+        }))
+        .listen(a, version_a)
+        .listen(b, version_b)
+        .listen(c, version_c)
+    }
+
+    /// Creates a read-only state which recomputes on each change of any of the same type sources
+    pub fn derive_all<S, F>(sources: &[&dyn DeriveSource<S>], f: F) -> DerivedState<T>
+    where
+        S: Clone + Send + Sync + 'static,
+        F: Fn(&[Arc<S>]) -> T + Send + Sync + 'static,
+    {
+        let sources: Vec<_> = sources
+            .iter()
+            .map(|source| (source.source(), source.source().version()))
+            .collect();
+        let weaks: Vec<_> = sources
+            .iter()
+            .map(|(source, _)| Arc::downgrade(source))
+            .collect();
+
+        let derived = DerivedState::new(Arc::new(move || {
+            let values = weaks
+                .iter()
+                .map(|weak| Some(weak.upgrade()?.swap.load_full()))
+                .collect::<Option<Vec<_>>>()?;
+            Some(f(&values))
+        }));
+
+        sources
+            .into_iter()
+            .fold(derived, |derived, (source, version)| {
+                derived.listen(source, version)
+            })
+    }
+}
+
+impl<A, B> State<(Arc<A>, Arc<B>)>
+where
+    A: Clone + Send + Sync + 'static,
+    B: Clone + Send + Sync + 'static,
+{
+    /// Creates a read-only state which combines two sources into a tuple
+    pub fn combine(
+        a: &impl DeriveSource<A>,
+        b: &impl DeriveSource<B>,
+    ) -> DerivedState<(Arc<A>, Arc<B>)> {
+        let (a, b) = (a.source(), b.source());
+        let (version_a, version_b) = (a.version(), b.version());
+        let (weak_a, weak_b) = (Arc::downgrade(a), Arc::downgrade(b));
+
+        DerivedState::new(Arc::new(move || {
+            Some((
+                weak_a.upgrade()?.swap.load_full(),
+                weak_b.upgrade()?.swap.load_full(),
+            ))
+        }))
+        .listen(a, version_a)
+        .listen(b, version_b)
+    }
+}
+
+/// The read-only state derived from other states
+pub struct DerivedState<T: Clone + Send + Sync> {
+    view: StateView<T>,
+    compute: Compute<T>,
+    updating: Arc<Mutex<()>>,
+    sources: Vec<Arc<dyn Any + Send + Sync>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> DerivedState<T> {
+    /// Creates a new derived state with computed initial value
+    fn new(compute: Compute<T>) -> Self {
+        let value = compute().expect("The derive sources are alive");
+
+        Self {
+            view: State::from(value).view(),
+            compute,
+            updating: Arc::new(Mutex::new(())),
+            sources: vec![],
+        }
+    }
+
+    /// Recomputes & publishes the value (the concurrent source changes are serialized to not publish a stale value)
+    fn update(target: &StateWrap<T>, compute: &Compute<T>, updating: &Mutex<()>) {
+        let _lock = updating.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = compute() {
+            target.publish(Arc::new(value));
+        }
+    }
+
+    /// Subscribes to the source changes ('version' is the source version before initial computing)
+    fn listen<S: Clone + Send + Sync + 'static>(
+        mut self,
+        source: &Arc<StateWrap<S>>,
+        version: u64,
+    ) -> Self {
+        let target = Arc::downgrade(&self.view.wrap);
+        let compute = self.compute.clone();
+        let updating = self.updating.clone();
+
+        source.listen(Box::new(move || {
+            let Some(target) = target.upgrade() else {
+                return false;
+            };
+            Self::update(&target, &compute, &updating);
+            true
+        }));
+
+        // the source could be changed before subscription:
+        if source.version() != version {
+            Self::update(&self.view.wrap, &self.compute, &self.updating);
+        }
+
+        // keep the source alive while the derived state exists:
+        self.sources.push(source.clone());
+        self
+    }
+}
+
+impl<T: Clone + Send + Sync> ::std::ops::Deref for DerivedState<T> {
+    type Target = StateView<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
+
+impl<T: Clone + Send + Sync + Debugging> ::std::fmt::Debug for DerivedState<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.view)
+    }
+}
+
+impl<T: Clone + Send + Sync + Displaying> ::std::fmt::Display for DerivedState<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &self.view)
+    }
+}
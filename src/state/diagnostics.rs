@@ -0,0 +1,139 @@
+use super::ERR_MSG;
+use crate::logger::warn;
+use crate::prelude::*;
+use std::{
+    panic::Location,
+    sync::Weak,
+    time::{Duration, Instant},
+};
+
+/// The guard holding time threshold (in milliseconds)
+static HOLD_THRESHOLD: AtomicU64 = AtomicU64::new(1000);
+/// The unlock waiting time threshold (in milliseconds)
+static WAIT_THRESHOLD: AtomicU64 = AtomicU64::new(1000);
+
+/// The actual state guard holder slot
+type HolderSlot = Mutex<Option<Holder>>;
+
+/// The watched state guard holders (checked by the watchdog thread)
+static WATCHED: Lazy<Mutex<Vec<Weak<HolderSlot>>>> = Lazy::new(|| {
+    std::thread::spawn(watchdog);
+    Mutex::new(vec![])
+});
+
+/// The state locks diagnostics settings
+pub struct Diagnostics;
+
+impl Diagnostics {
+    /// Returns the guard holding time threshold
+    pub fn hold_threshold() -> Duration {
+        Duration::from_millis(HOLD_THRESHOLD.load(Ordering::Relaxed))
+    }
+
+    /// Sets the guard holding time threshold (warns if the guard is held longer)
+    pub fn set_hold_threshold(threshold: Duration) {
+        HOLD_THRESHOLD.store(threshold.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the unlock waiting time threshold
+    pub fn wait_threshold() -> Duration {
+        Duration::from_millis(WAIT_THRESHOLD.load(Ordering::Relaxed))
+    }
+
+    /// Sets the unlock waiting time threshold (warns if a task waits longer)
+    pub fn set_wait_threshold(threshold: Duration) {
+        WAIT_THRESHOLD.store(threshold.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Warns about the guards which are held over threshold & not released yet
+fn watchdog() {
+    loop {
+        let threshold = Diagnostics::hold_threshold();
+        std::thread::sleep((threshold / 4).max(Duration::from_millis(10)));
+
+        WATCHED.lock().expect(ERR_MSG).retain(|holder| {
+            let Some(holder) = holder.upgrade() else {
+                return false;
+            };
+
+            if let Some(holder) = &mut *holder.lock().expect(ERR_MSG)
+                && !holder.reported
+                && holder.since.elapsed() > threshold
+            {
+                holder.reported = true;
+                warn!(
+                    "State guard acquired at {} is held over {threshold:?} (not released yet)",
+                    holder.location
+                );
+            }
+            true
+        });
+    }
+}
+
+/// The state guard holder
+struct Holder {
+    location: &'static Location<'static>,
+    since: Instant,
+    reported: bool,
+}
+
+/// The state lock tracker
+pub(super) struct Tracker {
+    holder: Arc<HolderSlot>,
+    watched: AtomicBool,
+}
+
+impl Tracker {
+    /// Creates a new lock tracker
+    pub(super) fn new() -> Self {
+        Self {
+            holder: Arc::new(Mutex::new(None)),
+            watched: AtomicBool::new(false),
+        }
+    }
+
+    /// Records the state guard acquisition (the holding is watched since the first acquisition)
+    pub(super) fn acquired(&self, location: &'static Location<'static>) {
+        *self.holder.lock().expect(ERR_MSG) = Some(Holder {
+            location,
+            since: Instant::now(),
+            reported: false,
+        });
+
+        if !self.watched.swap(true, Ordering::Relaxed) {
+            WATCHED
+                .lock()
+                .expect(ERR_MSG)
+                .push(Arc::downgrade(&self.holder));
+        }
+    }
+
+    /// Records the state guard release (warns about the long holding)
+    pub(super) fn released(&self) {
+        let Some(holder) = self.holder.lock().expect(ERR_MSG).take() else {
+            return;
+        };
+
+        let held = holder.since.elapsed();
+        if held > Diagnostics::hold_threshold() {
+            warn!(
+                "State guard acquired at {} was held for {held:?}",
+                holder.location
+            );
+        }
+    }
+
+    /// Warns about the long waiting for state unlock
+    pub(super) fn waiting(&self, waited: Duration) {
+        match &*self.holder.lock().expect(ERR_MSG) {
+            Some(holder) => warn!(
+                "Task waits for state unlock over {waited:?} (guard acquired at {} is held for {:?})",
+                holder.location,
+                holder.since.elapsed()
+            ),
+            None => warn!("Task waits for state unlock over {waited:?}"),
+        }
+    }
+}
@@ -1,22 +1,47 @@
-use super::ERR_MSG;
-use crate::flag::Flag;
+use super::{ERR_MSG, StateWrap};
 use crate::prelude::*;
+use std::panic::Location;
 
 /// The atomic state guard
 pub struct StateGuard<T: Clone + Send + Sync> {
-    pub(super) mutex: Arc<Mutex<Arc<T>>>,
-    pub(super) swap: Arc<ArcSwapAny<Arc<T>>>,
-    pub(super) data: T,
-    pub(super) lock: Arc<Flag>,
+    pub(super) wrap: Arc<StateWrap<T>>,
+    pub(super) data: Option<T>,
+    pub(super) locked: bool,
+    pub(super) changed: bool,
+    pub(super) location: &'static Location<'static>,
+}
+
+impl<T: Clone + Send + Sync> StateGuard<T> {
+    /// Returns the source location where the guard was acquired
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Publishes the changes immediately (the guard stays locked)
+    pub fn commit(&mut self) {
+        if self.changed {
+            self.wrap.publish(Arc::new((**self).clone()));
+            self.changed = false;
+        }
+    }
+
+    /// Discards the uncommitted changes & unlocks the state
+    pub fn abort(mut self) {
+        self.data.take();
+    }
 }
 
 impl<T: Clone + Send + Sync> ::std::ops::Drop for StateGuard<T> {
     fn drop(&mut self) {
-        let data = Arc::new(self.data.clone());
+        if let Some(data) = self.data.take()
+            && self.changed
+        {
+            self.wrap.publish(Arc::new(data));
+        }
 
-        *self.mutex.lock().expect(ERR_MSG) = data.clone();
-        self.swap.store(data);
-        self.lock.set(false);
+        if self.locked {
+            self.wrap.released();
+        }
     }
 }
 
@@ -24,24 +49,25 @@ impl<T: Clone + Send + Sync> ::std::ops::Deref for StateGuard<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.data
+        self.data.as_ref().expect(ERR_MSG)
     }
 }
 
 impl<T: Clone + Send + Sync> ::std::ops::DerefMut for StateGuard<T> {
     fn deref_mut(&mut self) -> &mut T {
-        &mut self.data
+        self.changed = true;
+        self.data.as_mut().expect(ERR_MSG)
     }
 }
 
 impl<T: Clone + Send + Sync + ::std::fmt::Debug> ::std::fmt::Debug for StateGuard<T> {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-        write!(f, "{:?}", &self.data)
+        write!(f, "{:?}", &**self)
     }
 }
 
 impl<T: Clone + Send + Sync + ::std::fmt::Display> ::std::fmt::Display for StateGuard<T> {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-        write!(f, "{}", &self.data)
+        write!(f, "{}", &**self)
     }
 }
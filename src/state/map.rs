@@ -0,0 +1,442 @@
+use super::{ERR_MSG, State, StateGuard, StateReceiver};
+use crate::prelude::*;
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, panic::Location};
+
+/// The atomic map of keyed states
+pub struct StateMap<K, V: Clone + Send + Sync> {
+    map: Lazy<Mutex<HashMap<K, Arc<State<V>>>>>,
+    inserted: Lazy<Notify>,
+    condvar: Condvar,
+}
+
+impl<K, V> StateMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    /// Creates a new state map
+    pub const fn new() -> Self {
+        Self {
+            map: Lazy::new(|| Mutex::new(HashMap::new())),
+            inserted: Lazy::new(Notify::new),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Returns the state of key
+    fn state<Q>(&self, key: &Q) -> Option<Arc<State<V>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lock_map().get(key).cloned()
+    }
+
+    /// Returns true if the state is still stored by key (not removed or replaced)
+    fn is_current<Q>(&self, key: &Q, state: &Arc<State<V>>) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lock_map()
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, state))
+    }
+
+    /// Returns the state of key (inserts a new state if not exists)
+    fn state_or_insert(&self, key: K, default: impl FnOnce() -> V) -> Arc<State<V>> {
+        let mut map = self.lock_map();
+        if let Some(state) = map.get(&key) {
+            return state.clone();
+        }
+
+        let state = Arc::new(State::from(default()));
+        map.insert(key, state.clone());
+        self.notify_inserted();
+        state
+    }
+
+    /// Inserts a new state of key (returns the existing state & value back if key exists)
+    fn try_insert(&self, key: K, value: V) -> Option<(Arc<State<V>>, V)> {
+        let mut map = self.lock_map();
+
+        match map.get(&key) {
+            Some(state) => Some((state.clone(), value)),
+            None => {
+                map.insert(key, Arc::new(State::from(value)));
+                self.notify_inserted();
+                None
+            }
+        }
+    }
+
+    /// Wakes the key insertion waiters
+    fn notify_inserted(&self) {
+        self.inserted.notify_waiters();
+        self.condvar.notify_all();
+    }
+
+    /// Locks the inner map
+    fn lock_map(&self) -> MutexGuard<'_, HashMap<K, Arc<State<V>>>> {
+        self.map.lock().expect(ERR_MSG)
+    }
+
+    /// Returns the number of keys
+    pub fn len(&self) -> usize {
+        self.lock_map().len()
+    }
+
+    /// Returns true if map has no keys
+    pub fn is_empty(&self) -> bool {
+        self.lock_map().is_empty()
+    }
+
+    /// Returns true if map contains the key
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lock_map().contains_key(key)
+    }
+
+    /// Returns the map keys
+    pub fn keys(&self) -> Vec<K> {
+        self.lock_map().keys().cloned().collect()
+    }
+
+    /// Returns the map values (warning: may not contain actual data)
+    pub fn dirty_snapshot(&self) -> HashMap<K, Arc<V>> {
+        self.lock_map()
+            .iter()
+            .map(|(key, state)| (key.clone(), state.dirty_get()))
+            .collect()
+    }
+
+    /// Returns a value of key
+    pub async fn get<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Some(self.state(key)?.get().await)
+    }
+
+    /// Returns a value of key (with synchronously blocking)
+    pub fn blocking_get<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Some(self.state(key)?.blocking_get())
+    }
+
+    /// Returns a value of key (warning: may not contain actual data)
+    pub fn dirty_get<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Some(self.state(key)?.dirty_get())
+    }
+
+    /// Sets a value of key (returns previous value)
+    pub async fn insert(&self, key: K, mut value: V) -> Option<Arc<V>> {
+        loop {
+            let (state, rest) = self.try_insert(key.clone(), value)?;
+            state.wrap().acquire().await;
+
+            // the key could be removed while waiting for the lock:
+            if self.is_current(&key, &state) {
+                return Some(Self::replace(&state, rest));
+            }
+            state.wrap().release();
+            value = rest;
+        }
+    }
+
+    /// Sets a value of key (with synchronously blocking)
+    pub fn blocking_insert(&self, key: K, mut value: V) -> Option<Arc<V>> {
+        loop {
+            let (state, rest) = self.try_insert(key.clone(), value)?;
+            state.wrap().blocking_acquire();
+
+            // the key could be removed while waiting for the lock:
+            if self.is_current(&key, &state) {
+                return Some(Self::replace(&state, rest));
+            }
+            state.wrap().release();
+            value = rest;
+        }
+    }
+
+    /// Sets a value of key (warning: changes not be saved if one of StateGuard is alive)
+    pub fn dirty_insert(&self, key: K, value: V) -> Option<Arc<V>> {
+        let (state, value) = self.try_insert(key, value)?;
+        let prev = state.dirty_get();
+        state.dirty_set(value);
+        Some(prev)
+    }
+
+    /// Replaces the value of locked state & unlocks it
+    fn replace(state: &State<V>, value: V) -> Arc<V> {
+        let prev = state.dirty_get();
+        state.dirty_set(value);
+        state.wrap().release();
+        prev
+    }
+
+    /// Writes a value of key directly (returns false if key not exists)
+    #[track_caller]
+    pub fn update<Q>(&self, key: &Q, f: impl FnOnce(&mut V)) -> impl Future<Output = bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let lock = self.lock(key);
+        async move {
+            match lock.await {
+                Some(mut guard) => {
+                    f(&mut guard);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    /// Writes a value of key directly (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_update<Q>(&self, key: &Q, f: impl FnOnce(&mut V)) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.blocking_lock(key) {
+            Some(mut guard) => {
+                f(&mut guard);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Writes a value of key directly (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
+    pub fn dirty_update<Q>(&self, key: &Q, f: impl FnOnce(&mut V)) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.state(key) {
+            Some(state) => {
+                state.dirty_map(f);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a key (returns the last value)
+    pub async fn remove<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let state = self.lock_map().remove(key)?;
+        let value = state.get().await;
+        state.wrap().close();
+        Some(value)
+    }
+
+    /// Removes a key (with synchronously blocking)
+    pub fn blocking_remove<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let state = self.lock_map().remove(key)?;
+        let value = state.blocking_get();
+        state.wrap().close();
+        Some(value)
+    }
+
+    /// Removes a key (warning: may not contain actual data)
+    pub fn dirty_remove<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let state = self.lock_map().remove(key)?;
+        let value = state.dirty_get();
+        state.wrap().close();
+        Some(value)
+    }
+
+    /// Removes all keys
+    pub fn clear(&self) {
+        let states: Vec<_> = self.lock_map().drain().collect();
+        for (_, state) in states {
+            state.wrap().close();
+        }
+    }
+
+    /// Returns a state guard of key
+    #[track_caller]
+    pub fn lock<Q>(&self, key: &Q) -> impl Future<Output = Option<StateGuard<V>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let location = Location::caller();
+        async move {
+            loop {
+                let state = self.state(key)?;
+                let guard = state.lock_at(location).await;
+
+                // the key could be removed while waiting for the lock:
+                if self.is_current(key, &state) {
+                    return Some(guard);
+                }
+                guard.abort();
+            }
+        }
+    }
+
+    /// Returns a state guard of key (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_lock<Q>(&self, key: &Q) -> Option<StateGuard<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        loop {
+            let state = self.state(key)?;
+            let guard = state.blocking_lock();
+
+            // the key could be removed while waiting for the lock:
+            if self.is_current(key, &state) {
+                return Some(guard);
+            }
+            guard.abort();
+        }
+    }
+
+    /// Returns a state guard of key (inserts the default value if key not exists)
+    #[track_caller]
+    pub fn entry(&self, key: K, default: impl Fn() -> V) -> impl Future<Output = StateGuard<V>> {
+        let location = Location::caller();
+        async move {
+            loop {
+                let state = self.state_or_insert(key.clone(), &default);
+                let guard = state.lock_at(location).await;
+
+                // the key could be removed while waiting for the lock:
+                if self.is_current(&key, &state) {
+                    return guard;
+                }
+                guard.abort();
+            }
+        }
+    }
+
+    /// Returns a state guard of key (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_entry(&self, key: K, default: impl Fn() -> V) -> StateGuard<V> {
+        loop {
+            let state = self.state_or_insert(key.clone(), &default);
+            let guard = state.blocking_lock();
+
+            // the key could be removed while waiting for the lock:
+            if self.is_current(&key, &state) {
+                return guard;
+            }
+            guard.abort();
+        }
+    }
+
+    /// Returns a state guard of key (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
+    pub fn dirty_entry(&self, key: K, default: impl FnOnce() -> V) -> StateGuard<V> {
+        self.state_or_insert(key, default).dirty_lock()
+    }
+
+    /// Returns a receiver of key value changes (the receiver is closed on key removal)
+    pub fn subscribe<Q>(&self, key: &Q) -> Option<StateReceiver<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Some(self.state(key)?.subscribe())
+    }
+
+    /// Waits for the key insertion & returns a receiver of key value changes
+    pub async fn wait_key<Q>(&self, key: &Q) -> StateReceiver<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        loop {
+            // subscribe before checking to avoid missing the notification:
+            let notified = self.inserted.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(rx) = self.subscribe(key) {
+                return rx;
+            }
+            notified.await;
+        }
+    }
+
+    /// Waits for the key insertion & returns a receiver of key value changes (with synchronously blocking)
+    pub fn blocking_wait_key<Q>(&self, key: &Q) -> StateReceiver<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut map = self.lock_map();
+
+        loop {
+            if let Some(state) = map.get(key) {
+                return state.subscribe();
+            }
+            map = self.condvar.wait(map).expect(ERR_MSG);
+        }
+    }
+}
+
+impl<K, V> ::std::default::Default for StateMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ::std::fmt::Debug for StateMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + Debugging,
+    V: Clone + Send + Sync + Debugging,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.dirty_snapshot())
+    }
+}
+
+impl<K, V> ::std::convert::From<HashMap<K, V>> for StateMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn from(map: HashMap<K, V>) -> Self {
+        let this = Self::new();
+        this.lock_map().extend(
+            map.into_iter()
+                .map(|(key, value)| (key, Arc::new(State::from(value)))),
+        );
+        this
+    }
+}
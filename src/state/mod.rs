@@ -1,79 +1,357 @@
 pub mod guard;
 pub use guard::StateGuard;
+pub mod receiver;
+pub use receiver::StateReceiver;
+pub mod versioned;
+use versioned::History;
+pub use versioned::{Snapshot, VersionedState};
+pub mod derive;
+pub use derive::{DeriveSource, DerivedState};
+pub mod view;
+pub use view::StateView;
+pub mod project;
+pub use project::{Projected, ProjectedGuard, Projection, ProjectionMut};
+pub mod map;
+pub use map::StateMap;
+pub mod queue;
+pub use queue::{Overflow, StateQueue};
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::Diagnostics;
+#[cfg(feature = "diagnostics")]
+use diagnostics::Tracker;
 
 pub(super) const ERR_MSG: &str = "The data has been poisoned!";
 
 use crate::flag::Flag;
 use crate::prelude::*;
+use arc_swap::Guard;
+use once_cell::sync::OnceCell;
+use std::panic::Location;
+use std::time::{Duration, Instant};
 
 /// The atomic state wrapper
-#[derive(Clone)]
-pub struct StateWrap<T: Default + Clone + Send + Sync> {
-    mutex: Arc<Mutex<Arc<T>>>,
-    swap: Arc<ArcSwapAny<Arc<T>>>,
-    lock: Arc<Flag>,
+pub struct StateWrap<T: Clone + Send + Sync> {
+    mutex: Mutex<Arc<T>>,
+    swap: ArcSwapAny<Arc<T>>,
+    lock: Flag,
+    version: AtomicU64,
+    notify: Notify,
+    condvar: Condvar,
+    history: Option<Mutex<History<T>>>,
+    listeners: Mutex<Vec<Listener>>,
+    closed: AtomicBool,
+    #[cfg(feature = "diagnostics")]
+    tracker: Tracker,
+}
+
+/// The state change listener (returns false to unsubscribe)
+type Listener = Box<dyn Fn() -> bool + Send + Sync>;
+
+impl<T: Clone + Send + Sync> StateWrap<T> {
+    /// Creates a new state wrapper (with history of values if capacity is not zero)
+    fn new(value: T, history: usize) -> Self {
+        let arc_val = Arc::new(value);
+        Self {
+            history: (history > 0).then(|| Mutex::new(History::new(history, arc_val.clone()))),
+            mutex: Mutex::new(arc_val.clone()),
+            swap: ArcSwapAny::from(arc_val),
+            lock: Flag::from(false),
+            version: AtomicU64::new(0),
+            notify: Notify::new(),
+            condvar: Condvar::new(),
+            listeners: Mutex::new(vec![]),
+            closed: AtomicBool::new(false),
+            #[cfg(feature = "diagnostics")]
+            tracker: Tracker::new(),
+        }
+    }
+
+    /// Returns the actual state version
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Tries to acquire the exclusive state lock
+    fn try_acquire(&self) -> bool {
+        self.lock.try_acquire()
+    }
+
+    /// Acquires the exclusive state lock
+    async fn acquire(&self) {
+        while !self.try_acquire() {
+            self.wait_unlock().await;
+        }
+    }
+
+    /// Acquires the exclusive state lock (with synchronously blocking)
+    fn blocking_acquire(&self) {
+        while !self.try_acquire() {
+            self.blocking_wait_unlock();
+        }
+    }
+
+    /// Waits for the exclusive state lock release
+    async fn wait_unlock(&self) {
+        #[cfg(feature = "diagnostics")]
+        {
+            let threshold = Diagnostics::wait_threshold();
+            if self.lock.wait_timeout(false, threshold).await {
+                return;
+            }
+            self.tracker.waiting(threshold);
+        }
+        self.lock.wait(false).await;
+    }
+
+    /// Waits for the exclusive state lock release (with synchronously blocking)
+    fn blocking_wait_unlock(&self) {
+        #[cfg(feature = "diagnostics")]
+        {
+            let threshold = Diagnostics::wait_threshold();
+            if self.lock.blocking_wait_timeout(false, threshold) {
+                return;
+            }
+            self.tracker.waiting(threshold);
+        }
+        self.lock.blocking_wait(false);
+    }
+
+    /// Acquires the exclusive state lock by interval (with synchronously blocking)
+    fn blocking_acquire_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        while !self.try_acquire() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() || !self.lock.blocking_wait_timeout(false, left) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Releases the exclusive state lock
+    fn release(&self) {
+        self.lock.set(false);
+    }
+
+    /// Records the state guard acquisition
+    #[cfg_attr(not(feature = "diagnostics"), allow(unused_variables))]
+    fn acquired(&self, location: &'static Location<'static>) {
+        #[cfg(feature = "diagnostics")]
+        self.tracker.acquired(location);
+    }
+
+    /// Records the state guard release & releases the exclusive state lock
+    fn released(&self) {
+        #[cfg(feature = "diagnostics")]
+        self.tracker.released();
+        self.release();
+    }
+
+    /// Publishes a new value & notifies subscribers
+    fn publish(&self, value: Arc<T>) {
+        self.swap.store(value);
+        self.changed();
+    }
+
+    /// Notifies subscribers about the value change & records it to history
+    fn changed(&self) {
+        self.notify_changed(true);
+    }
+
+    /// Notifies subscribers about the value restored from history
+    fn restored(&self) {
+        self.notify_changed(false);
+    }
+
+    /// Notifies subscribers about the value change
+    fn notify_changed(&self, record: bool) {
+        let mut lock = self.mutex.lock().expect(ERR_MSG);
+        *lock = self.swap.load_full();
+
+        let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+        if record && let Some(history) = &self.history {
+            history.lock().expect(ERR_MSG).record(version, lock.clone());
+        }
+
+        self.condvar.notify_all();
+        self.notify.notify_waiters();
+        drop(lock);
+
+        self.listeners
+            .lock()
+            .expect(ERR_MSG)
+            .retain(|listener| listener());
+    }
+
+    /// Marks the state as closed & wakes the subscribers (the closing is not a value change)
+    fn close(&self) {
+        let lock = self.mutex.lock().expect(ERR_MSG);
+        self.closed.store(true, Ordering::SeqCst);
+
+        self.condvar.notify_all();
+        self.notify.notify_waiters();
+        drop(lock);
+    }
+
+    /// Returns true if the state is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Adds a state change listener
+    fn listen(&self, listener: Listener) {
+        self.listeners.lock().expect(ERR_MSG).push(listener);
+    }
+}
+
+/// The exclusive state lock releaser (releases the lock on drop, e.g. if the closure panics)
+struct Release<'a, T: Clone + Send + Sync>(&'a StateWrap<T>);
+
+impl<T: Clone + Send + Sync> ::std::ops::Drop for Release<'_, T> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
 }
 
 /// The atomic state
-pub struct State<T: Default + Clone + Send + Sync> {
-    wrap: Lazy<Arc<StateWrap<T>>>,
+pub struct State<T: Clone + Send + Sync> {
+    init: fn() -> T,
+    history: usize,
+    wrap: OnceCell<Arc<StateWrap<T>>>,
 }
 
 impl<T: Default + Clone + Send + Sync> State<T> {
     /// Creates a new state
     pub const fn new() -> Self {
+        Self::with_init(T::default)
+    }
+}
+
+impl<T: Clone + Send + Sync> State<T> {
+    /// Creates a new state with lazily computed initial value
+    pub const fn with_init(init: fn() -> T) -> Self {
+        Self::with_history(init, 0)
+    }
+
+    /// Creates a new state which keeps the last published values
+    const fn with_history(init: fn() -> T, capacity: usize) -> Self {
         Self {
-            wrap: Lazy::new(|| {
-                let arc_val = Arc::new(T::default());
-                Arc::new(StateWrap {
-                    mutex: Arc::new(Mutex::new(arc_val.clone())),
-                    swap: Arc::new(ArcSwapAny::from(arc_val)),
-                    lock: Arc::new(Flag::from(false)),
-                })
-            }),
+            init,
+            history: capacity,
+            wrap: OnceCell::new(),
         }
     }
 
+    /// Returns the state wrapper (initializes it on first access)
+    fn wrap(&self) -> &Arc<StateWrap<T>> {
+        self.wrap
+            .get_or_init(|| Arc::new(StateWrap::new((self.init)(), self.history)))
+    }
+
     /// Returns true if data locked by some StateGuard
     pub fn is_locked(&self) -> bool {
-        self.wrap.lock.is_true()
+        self.wrap().lock.is_true()
     }
 
     /// Waits for unlock state guard
     pub async fn wait_unlock(&self) {
         while self.is_locked() {
-            self.wrap.lock.wait(false).await;
+            self.wrap().wait_unlock().await;
         }
     }
 
     /// Waits for unlock state guard (with synchronously blocking)
     pub fn blocking_wait_unlock(&self) {
         while self.is_locked() {
-            self.wrap.lock.blocking_wait(false);
+            self.wrap().blocking_wait_unlock();
         }
     }
 
+    /// Waits for unlock state guard by interval (returns false on timeout)
+    pub async fn wait_unlock_timeout(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.wait_unlock())
+            .await
+            .is_ok()
+    }
+
+    /// Waits for unlock state guard by interval (with synchronously blocking)
+    pub fn blocking_wait_unlock_timeout(&self, timeout: Duration) -> bool {
+        self.wrap().lock.blocking_wait_timeout(false, timeout)
+    }
+
     /// Returns a state guard
-    pub async fn lock(&self) -> StateGuard<T> {
-        self.wait_unlock().await;
-        self.dirty_lock()
+    #[track_caller]
+    pub fn lock(&self) -> impl Future<Output = StateGuard<T>> {
+        self.lock_at(Location::caller())
+    }
+
+    /// Returns a state guard acquired at the source location
+    pub(super) async fn lock_at(&self, location: &'static Location<'static>) -> StateGuard<T> {
+        self.wrap().acquire().await;
+        self.new_guard(true, location)
     }
 
     /// Returns a state guard (with synchronously blocking)
+    #[track_caller]
     pub fn blocking_lock(&self) -> StateGuard<T> {
-        self.blocking_wait_unlock();
-        self.dirty_lock()
+        self.wrap().blocking_acquire();
+        self.new_guard(true, Location::caller())
+    }
+
+    /// Returns a state guard or None on timeout
+    #[track_caller]
+    pub fn lock_timeout(&self, timeout: Duration) -> impl Future<Output = Option<StateGuard<T>>> {
+        let lock = self.lock();
+        async move { tokio::time::timeout(timeout, lock).await.ok() }
+    }
+
+    /// Returns a state guard or None on timeout (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_lock_timeout(&self, timeout: Duration) -> Option<StateGuard<T>> {
+        if self.wrap().blocking_acquire_timeout(timeout) {
+            Some(self.new_guard(true, Location::caller()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a state guard or None if the cancellation future completes first
+    #[track_caller]
+    pub fn lock_or_cancel(
+        &self,
+        cancel: impl Future,
+    ) -> impl Future<Output = Option<StateGuard<T>>> {
+        let lock = self.lock();
+        async move {
+            tokio::select! {
+                guard = lock => Some(guard),
+                _ = cancel => None,
+            }
+        }
     }
 
     /// Returns a state guard (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
     pub fn dirty_lock(&self) -> StateGuard<T> {
-        self.wrap.lock.set(true);
+        let locked = self.wrap().try_acquire();
+        self.new_guard(locked, Location::caller())
+    }
+
+    /// Creates a new state guard
+    fn new_guard(&self, locked: bool, location: &'static Location<'static>) -> StateGuard<T> {
+        if locked {
+            self.wrap().acquired(location);
+        }
 
         StateGuard {
-            mutex: self.wrap.mutex.clone(),
-            swap: self.wrap.swap.clone(),
-            data: self.dirty_get_cloned(),
-            lock: self.wrap.lock.clone(),
+            wrap: self.wrap().clone(),
+            data: Some(self.dirty_get_cloned()),
+            locked,
+            changed: false,
+            location,
         }
     }
 
@@ -89,9 +367,31 @@ impl<T: Default + Clone + Send + Sync> State<T> {
         self.dirty_get()
     }
 
+    /// Returns a state value or None on timeout
+    pub async fn get_timeout(&self, timeout: Duration) -> Option<Arc<T>> {
+        tokio::time::timeout(timeout, self.get()).await.ok()
+    }
+
+    /// Returns a state value or None on timeout (with synchronously blocking)
+    pub fn blocking_get_timeout(&self, timeout: Duration) -> Option<Arc<T>> {
+        if self.blocking_wait_unlock_timeout(timeout) {
+            Some(self.dirty_get())
+        } else {
+            None
+        }
+    }
+
+    /// Returns a state value or None if the cancellation future completes first
+    pub async fn get_or_cancel(&self, cancel: impl Future) -> Option<Arc<T>> {
+        tokio::select! {
+            value = self.get() => Some(value),
+            _ = cancel => None,
+        }
+    }
+
     /// Returns a state value (warning: may not contain actual data)
     pub fn dirty_get(&self) -> Arc<T> {
-        self.wrap.swap.load_full()
+        self.wrap().swap.load_full()
     }
 
     /// Returns a clone of state value
@@ -108,85 +408,176 @@ impl<T: Default + Clone + Send + Sync> State<T> {
 
     /// Returns a clone of state value (warning: may not contain actual data)
     pub fn dirty_get_cloned(&self) -> T {
-        self.wrap.swap.load_full().as_ref().clone()
+        self.wrap().swap.load_full().as_ref().clone()
     }
 
     /// Sets a new value to state
     pub async fn set(&self, value: T) {
-        self.wait_unlock().await;
-        self.wrap.lock.set(true);
-
+        self.wrap().acquire().await;
         self.dirty_set(value);
-        self.wrap.lock.set(false);
+        self.wrap().release();
     }
 
     /// Sets a new value to state (with synchronously blocking)
     pub fn blocking_set(&self, value: T) {
-        self.blocking_wait_unlock();
-        self.wrap.lock.set(true);
-
+        self.wrap().blocking_acquire();
         self.dirty_set(value);
-        self.wrap.lock.set(false);
+        self.wrap().release();
     }
 
     /// Sets a new value to state (warning: changes not be saved if one of StateGuard is alive)
     pub fn dirty_set(&self, value: T) {
-        let new_data = Arc::new(value);
-        let mut lock = self.wrap.mutex.lock().unwrap();
-        *lock = new_data.clone();
-        self.wrap.swap.store(new_data);
+        self.wrap().publish(Arc::new(value));
     }
 
-    /// Writes data directly
-    pub async fn map(&self, f: impl FnOnce(&mut T)) {
-        let mut guard = self.lock().await;
-        let mut data = (*guard).clone();
+    /// Returns the state version (increments on each published change)
+    pub fn version(&self) -> u64 {
+        self.wrap().version()
+    }
+
+    /// Returns a receiver of state changes
+    pub fn subscribe(&self) -> StateReceiver<T> {
+        StateReceiver {
+            wrap: self.wrap().clone(),
+            version: self.wrap().version(),
+        }
+    }
 
-        f(&mut data);
-        *guard = data;
+    /// Writes data directly
+    #[track_caller]
+    pub fn map(&self, f: impl FnOnce(&mut T)) -> impl Future<Output = ()> {
+        let lock = self.lock();
+        async move {
+            let mut guard = lock.await;
+            f(&mut guard);
+        }
     }
 
     /// Writes data directrly (with synchronously blocking)
+    #[track_caller]
     pub fn blocking_map(&self, f: impl FnOnce(&mut T)) {
         let mut guard = self.blocking_lock();
-        let mut data = (*guard).clone();
-        f(&mut data);
-        *guard = data;
+        f(&mut guard);
     }
 
     /// Writes data directly (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
     pub fn dirty_map(&self, f: impl FnOnce(&mut T)) {
         let mut guard = self.dirty_lock();
-        let mut data = (*guard).clone();
+        f(&mut guard);
+    }
 
-        f(&mut data);
-        *guard = data;
+    /// Writes data directly (changes are saved only if the closure returns Ok)
+    #[track_caller]
+    pub fn try_map<R, E>(
+        &self,
+        f: impl FnOnce(&mut T) -> StdResult<R, E>,
+    ) -> impl Future<Output = StdResult<R, E>> {
+        let lock = self.lock();
+        async move { Self::try_map_guard(lock.await, f) }
+    }
+
+    /// Writes data directly if the closure returns Ok (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_try_map<R, E>(
+        &self,
+        f: impl FnOnce(&mut T) -> StdResult<R, E>,
+    ) -> StdResult<R, E> {
+        Self::try_map_guard(self.blocking_lock(), f)
+    }
+
+    /// Writes data directly if the closure returns Ok (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
+    pub fn dirty_try_map<R, E>(
+        &self,
+        f: impl FnOnce(&mut T) -> StdResult<R, E>,
+    ) -> StdResult<R, E> {
+        Self::try_map_guard(self.dirty_lock(), f)
+    }
+
+    /// Writes data by the state guard (aborts changes on Err)
+    fn try_map_guard<R, E>(
+        mut guard: StateGuard<T>,
+        f: impl FnOnce(&mut T) -> StdResult<R, E>,
+    ) -> StdResult<R, E> {
+        let result = f(&mut guard);
+        if result.is_err() {
+            guard.abort();
+        }
+        result
+    }
+
+    /// Updates data by read-copy-update without cloning (returns previous value)
+    pub async fn rcu(&self, f: impl Fn(&T) -> T) -> Arc<T> {
+        self.wrap().acquire().await;
+        let _release = Release(self.wrap());
+        self.dirty_rcu(f)
+    }
+
+    /// Updates data by read-copy-update (with synchronously blocking)
+    pub fn blocking_rcu(&self, f: impl Fn(&T) -> T) -> Arc<T> {
+        self.wrap().blocking_acquire();
+        let _release = Release(self.wrap());
+        self.dirty_rcu(f)
+    }
+
+    /// Updates data by read-copy-update (warning: changes not be saved if one of StateGuard is alive, closure may be called several times)
+    pub fn dirty_rcu(&self, f: impl Fn(&T) -> T) -> Arc<T> {
+        let prev = self.wrap().swap.rcu(|data| Arc::new(f(data)));
+        self.wrap().changed();
+        prev
+    }
+
+    /// Sets a new value if the actual value is 'expected' (returns previous value or actual on error)
+    pub async fn compare_and_set(&self, expected: &Arc<T>, new: T) -> StdResult<Arc<T>, Arc<T>> {
+        self.wrap().acquire().await;
+        let _release = Release(self.wrap());
+        self.dirty_compare_and_set(expected, new)
+    }
+
+    /// Sets a new value if the actual value is 'expected' (with synchronously blocking)
+    pub fn blocking_compare_and_set(&self, expected: &Arc<T>, new: T) -> StdResult<Arc<T>, Arc<T>> {
+        self.wrap().blocking_acquire();
+        let _release = Release(self.wrap());
+        self.dirty_compare_and_set(expected, new)
+    }
+
+    /// Sets a new value if the actual value is 'expected' (warning: changes not be saved if one of StateGuard is alive)
+    pub fn dirty_compare_and_set(&self, expected: &Arc<T>, new: T) -> StdResult<Arc<T>, Arc<T>> {
+        let prev = Guard::into_inner(self.wrap().swap.compare_and_swap(expected, Arc::new(new)));
+
+        if Arc::ptr_eq(&prev, expected) {
+            self.wrap().changed();
+            Ok(prev)
+        } else {
+            Err(prev)
+        }
     }
 }
 
 impl<T: Default + Clone + Send + Sync> ::std::default::Default for State<T> {
     fn default() -> Self {
-        let this = Self::new();
-        this.dirty_set(Default::default());
-        this
+        Self::new()
     }
 }
 
-impl<T: Default + Clone + Send + Sync> ::std::convert::From<T> for State<T> {
+impl<T: Clone + Send + Sync> ::std::convert::From<T> for State<T> {
     fn from(value: T) -> Self {
-        let this = Self::new();
-        this.dirty_set(value);
-        this
+        Self {
+            init: || unreachable!("The state is initialized by value"),
+            history: 0,
+            wrap: OnceCell::with_value(Arc::new(StateWrap::new(value, 0))),
+        }
     }
 }
 
-impl<T: Default + Clone + Send + Sync + Debugging> ::std::fmt::Debug for State<T> {
+impl<T: Clone + Send + Sync + Debugging> ::std::fmt::Debug for State<T> {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         write!(f, "{:?}", &self.dirty_get())
     }
 }
 
-impl<T: Default + Clone + Send + Sync + Displaying> ::std::fmt::Display for State<T> {
+impl<T: Clone + Send + Sync + Displaying> ::std::fmt::Display for State<T> {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         write!(f, "{}", &self.dirty_get())
     }
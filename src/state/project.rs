@@ -0,0 +1,256 @@
+use super::{ERR_MSG, State, StateView, StateWrap};
+use crate::prelude::*;
+use std::panic::Location;
+
+impl<T: Clone + Send + Sync> State<T> {
+    /// Returns a read-only projection to the part of state
+    pub fn project<U>(&self, get: fn(&T) -> &U) -> Projection<T, U> {
+        self.view().project(get)
+    }
+
+    /// Returns a lockable projection to the part of state (the guard clones only the part)
+    pub fn project_mut<U: Clone>(
+        &self,
+        get: fn(&T) -> &U,
+        get_mut: fn(&mut T) -> &mut U,
+    ) -> ProjectionMut<'_, T, U> {
+        ProjectionMut {
+            state: self,
+            get,
+            get_mut,
+        }
+    }
+}
+
+/// The read-only projection to the part of state
+pub struct Projection<T: Clone + Send + Sync, U> {
+    pub(super) view: StateView<T>,
+    pub(super) get: fn(&T) -> &U,
+}
+
+impl<T: Clone + Send + Sync, U> Projection<T, U> {
+    /// Returns a projected value
+    pub async fn get(&self) -> Projected<T, U> {
+        self.projected(self.view.get().await)
+    }
+
+    /// Returns a projected value (with synchronously blocking)
+    pub fn blocking_get(&self) -> Projected<T, U> {
+        self.projected(self.view.blocking_get())
+    }
+
+    /// Returns a projected value (warning: may not contain actual data)
+    pub fn dirty_get(&self) -> Projected<T, U> {
+        self.projected(self.view.dirty_get())
+    }
+
+    /// Returns the state view
+    pub fn view(&self) -> &StateView<T> {
+        &self.view
+    }
+
+    /// Wraps the state value into the projected value
+    fn projected(&self, data: Arc<T>) -> Projected<T, U> {
+        Projected {
+            data,
+            get: self.get,
+        }
+    }
+}
+
+/// The lockable projection to the part of state (the whole state is locked, but only the part is cloned)
+pub struct ProjectionMut<'a, T: Clone + Send + Sync, U: Clone> {
+    state: &'a State<T>,
+    get: fn(&T) -> &U,
+    get_mut: fn(&mut T) -> &mut U,
+}
+
+impl<T: Clone + Send + Sync, U: Clone> ProjectionMut<'_, T, U> {
+    /// Returns a projected value
+    pub async fn get(&self) -> Projected<T, U> {
+        self.projected(self.state.get().await)
+    }
+
+    /// Returns a projected value (with synchronously blocking)
+    pub fn blocking_get(&self) -> Projected<T, U> {
+        self.projected(self.state.blocking_get())
+    }
+
+    /// Returns a projected value (warning: may not contain actual data)
+    pub fn dirty_get(&self) -> Projected<T, U> {
+        self.projected(self.state.dirty_get())
+    }
+
+    /// Returns a projected state guard (the whole state is locked)
+    #[track_caller]
+    pub fn lock(&self) -> impl Future<Output = ProjectedGuard<T, U>> {
+        let location = Location::caller();
+        async move {
+            self.state.wrap().acquire().await;
+            self.guarded(true, location)
+        }
+    }
+
+    /// Returns a projected state guard (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_lock(&self) -> ProjectedGuard<T, U> {
+        self.state.wrap().blocking_acquire();
+        self.guarded(true, Location::caller())
+    }
+
+    /// Returns a projected state guard (warning: changes not be saved if one of StateGuard is alive)
+    #[track_caller]
+    pub fn dirty_lock(&self) -> ProjectedGuard<T, U> {
+        let locked = self.state.wrap().try_acquire();
+        self.guarded(locked, Location::caller())
+    }
+
+    /// Writes the projected data directly
+    #[track_caller]
+    pub fn map(&self, f: impl FnOnce(&mut U)) -> impl Future<Output = ()> {
+        let lock = self.lock();
+        async move { f(&mut *lock.await) }
+    }
+
+    /// Writes the projected data directly (with synchronously blocking)
+    #[track_caller]
+    pub fn blocking_map(&self, f: impl FnOnce(&mut U)) {
+        f(&mut *self.blocking_lock());
+    }
+
+    /// Wraps the state value into the projected value
+    fn projected(&self, data: Arc<T>) -> Projected<T, U> {
+        Projected {
+            data,
+            get: self.get,
+        }
+    }
+
+    /// Creates a new projected guard (clones only the projected part)
+    fn guarded(&self, locked: bool, location: &'static Location<'static>) -> ProjectedGuard<T, U> {
+        let wrap = self.state.wrap().clone();
+        if locked {
+            wrap.acquired(location);
+        }
+
+        ProjectedGuard {
+            data: Some((self.get)(&wrap.swap.load_full()).clone()),
+            wrap,
+            locked,
+            changed: false,
+            location,
+            get_mut: self.get_mut,
+        }
+    }
+}
+
+/// The projected state value
+pub struct Projected<T, U> {
+    data: Arc<T>,
+    get: fn(&T) -> &U,
+}
+
+impl<T, U> Projected<T, U> {
+    /// Returns the whole state value
+    pub fn into_inner(self) -> Arc<T> {
+        self.data
+    }
+}
+
+impl<T, U> ::std::ops::Deref for Projected<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        (self.get)(&self.data)
+    }
+}
+
+impl<T, U: Debugging> ::std::fmt::Debug for Projected<T, U> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &**self)
+    }
+}
+
+impl<T, U: Displaying> ::std::fmt::Display for Projected<T, U> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &**self)
+    }
+}
+
+/// The projected state guard (the changed part is written back to a single clone of state value)
+pub struct ProjectedGuard<T: Clone + Send + Sync, U: Clone> {
+    wrap: Arc<StateWrap<T>>,
+    data: Option<U>,
+    locked: bool,
+    changed: bool,
+    location: &'static Location<'static>,
+    get_mut: fn(&mut T) -> &mut U,
+}
+
+impl<T: Clone + Send + Sync, U: Clone> ProjectedGuard<T, U> {
+    /// Returns the source location where the guard was acquired
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Publishes the changes immediately (the guard stays locked)
+    pub fn commit(&mut self) {
+        if self.changed {
+            self.publish((**self).clone());
+            self.changed = false;
+        }
+    }
+
+    /// Discards the uncommitted changes & unlocks the state
+    pub fn abort(mut self) {
+        self.data.take();
+    }
+
+    /// Writes the part into a clone of actual state value & publishes it
+    fn publish(&self, data: U) {
+        let mut value = self.wrap.swap.load_full().as_ref().clone();
+        *(self.get_mut)(&mut value) = data;
+        self.wrap.publish(Arc::new(value));
+    }
+}
+
+impl<T: Clone + Send + Sync, U: Clone> ::std::ops::Drop for ProjectedGuard<T, U> {
+    fn drop(&mut self) {
+        if let Some(data) = self.data.take()
+            && self.changed
+        {
+            self.publish(data);
+        }
+
+        if self.locked {
+            self.wrap.released();
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync, U: Clone> ::std::ops::Deref for ProjectedGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.data.as_ref().expect(ERR_MSG)
+    }
+}
+
+impl<T: Clone + Send + Sync, U: Clone> ::std::ops::DerefMut for ProjectedGuard<T, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        self.changed = true;
+        self.data.as_mut().expect(ERR_MSG)
+    }
+}
+
+impl<T: Clone + Send + Sync, U: Clone + Debugging> ::std::fmt::Debug for ProjectedGuard<T, U> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &**self)
+    }
+}
+
+impl<T: Clone + Send + Sync, U: Clone + Displaying> ::std::fmt::Display for ProjectedGuard<T, U> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &**self)
+    }
+}
@@ -0,0 +1,231 @@
+use super::ERR_MSG;
+use crate::flag::Waiter;
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+/// The queue overflow policy
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Overflow {
+    /// Drops the oldest item to free space
+    DropOldest,
+    /// Drops the pushed item
+    DropNewest,
+    /// Waits until space is available
+    Block,
+}
+
+/// The atomic queue wrapper
+pub struct QueueWrap<T> {
+    items: Mutex<VecDeque<T>>,
+    waiter: Waiter,
+}
+
+/// The atomic queue
+pub struct StateQueue<T: Send> {
+    capacity: usize,
+    overflow: Overflow,
+    wrap: Lazy<QueueWrap<T>>,
+}
+
+impl<T: Send> StateQueue<T> {
+    /// Creates a new unbounded queue
+    pub const fn new() -> Self {
+        Self::bounded(0, Overflow::Block)
+    }
+
+    /// Creates a new queue with limited capacity (zero means unbounded)
+    pub const fn bounded(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            capacity,
+            overflow,
+            wrap: Lazy::new(|| QueueWrap {
+                items: Mutex::new(VecDeque::new()),
+                waiter: Waiter::new(),
+            }),
+        }
+    }
+
+    /// Returns the queue capacity (zero means unbounded)
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the queue overflow policy
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Returns the number of items
+    pub fn len(&self) -> usize {
+        self.lock_items().len()
+    }
+
+    /// Returns true if queue has no items
+    pub fn is_empty(&self) -> bool {
+        self.lock_items().is_empty()
+    }
+
+    /// Returns true if queue has no free space
+    pub fn is_full(&self) -> bool {
+        self.capacity > 0 && self.len() >= self.capacity
+    }
+
+    /// Pushes an item to the queue end (returns the dropped item on overflow)
+    pub async fn push(&self, item: T) -> Option<T> {
+        let mut item = item;
+        loop {
+            match self.try_push(item) {
+                Ok(dropped) => return dropped,
+                Err(back) => item = back,
+            }
+            self.wrap.waiter.wait(|| !self.is_full()).await;
+        }
+    }
+
+    /// Pushes an item to the queue end (with synchronously blocking)
+    pub fn blocking_push(&self, item: T) -> Option<T> {
+        let mut item = item;
+        loop {
+            match self.try_push(item) {
+                Ok(dropped) => return dropped,
+                Err(back) => item = back,
+            }
+            self.wrap.waiter.blocking_wait(|| !self.is_full());
+        }
+    }
+
+    /// Pushes an item without waiting (returns the item back if queue is full with 'Block' policy)
+    pub fn try_push(&self, item: T) -> StdResult<Option<T>, T> {
+        let result = self.push_item(&mut self.lock_items(), item);
+        if !matches!(result, Ok(Some(_)) if self.overflow == Overflow::DropNewest) {
+            self.wrap.waiter.notify();
+        }
+        result
+    }
+
+    /// Pushes items to the queue end at once (returns the dropped items on overflow)
+    pub async fn extend(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        let mut items = items.into_iter();
+        let mut dropped = vec![];
+        let mut blocked = None;
+
+        loop {
+            blocked = self.push_many(blocked.into_iter().chain(&mut items), &mut dropped);
+            if blocked.is_none() {
+                return dropped;
+            }
+            self.wrap.waiter.wait(|| !self.is_full()).await;
+        }
+    }
+
+    /// Pushes items to the queue end at once (with synchronously blocking)
+    pub fn blocking_extend(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        let mut items = items.into_iter();
+        let mut dropped = vec![];
+        let mut blocked = None;
+
+        loop {
+            blocked = self.push_many(blocked.into_iter().chain(&mut items), &mut dropped);
+            if blocked.is_none() {
+                return dropped;
+            }
+            self.wrap.waiter.blocking_wait(|| !self.is_full());
+        }
+    }
+
+    /// Pushes items while there is a free space (returns the item which has no place with 'Block' policy)
+    fn push_many(&self, items: impl Iterator<Item = T>, dropped: &mut Vec<T>) -> Option<T> {
+        let mut queue = self.lock_items();
+        let mut blocked = None;
+
+        for item in items {
+            match self.push_item(&mut queue, item) {
+                Ok(item) => dropped.extend(item),
+                Err(item) => {
+                    blocked = Some(item);
+                    break;
+                }
+            }
+        }
+        drop(queue);
+
+        self.wrap.waiter.notify();
+        blocked
+    }
+
+    /// Pushes an item by the overflow policy (returns the item back if there is no place with 'Block' policy)
+    fn push_item(&self, queue: &mut VecDeque<T>, item: T) -> StdResult<Option<T>, T> {
+        let mut dropped = None;
+
+        if self.capacity > 0 && queue.len() >= self.capacity {
+            match self.overflow {
+                Overflow::DropOldest => dropped = queue.pop_front(),
+                Overflow::DropNewest => return Ok(Some(item)),
+                Overflow::Block => return Err(item),
+            }
+        }
+        queue.push_back(item);
+
+        Ok(dropped)
+    }
+
+    /// Pops an item from the queue start (waits until available)
+    pub async fn pop(&self) -> T {
+        loop {
+            if let Some(item) = self.try_pop() {
+                return item;
+            }
+            self.wrap.waiter.wait(|| !self.is_empty()).await;
+        }
+    }
+
+    /// Pops an item from the queue start (with synchronously blocking)
+    pub fn blocking_pop(&self) -> T {
+        loop {
+            if let Some(item) = self.try_pop() {
+                return item;
+            }
+            self.wrap.waiter.blocking_wait(|| !self.is_empty());
+        }
+    }
+
+    /// Pops an item from the queue start without waiting
+    pub fn try_pop(&self) -> Option<T> {
+        let item = self.lock_items().pop_front();
+        if item.is_some() {
+            self.wrap.waiter.notify();
+        }
+        item
+    }
+
+    /// Takes all the queue items
+    pub fn drain(&self) -> Vec<T> {
+        let items = self.lock_items().drain(..).collect::<Vec<_>>();
+        if !items.is_empty() {
+            self.wrap.waiter.notify();
+        }
+        items
+    }
+
+    /// Removes all the queue items
+    pub fn clear(&self) {
+        self.drain();
+    }
+
+    /// Locks the queue items
+    fn lock_items(&self) -> MutexGuard<'_, VecDeque<T>> {
+        self.wrap.items.lock().expect(ERR_MSG)
+    }
+}
+
+impl<T: Send> ::std::default::Default for StateQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + Debugging> ::std::fmt::Debug for StateQueue<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &*self.lock_items())
+    }
+}
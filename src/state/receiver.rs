@@ -0,0 +1,87 @@
+use super::{ERR_MSG, StateWrap};
+use crate::prelude::*;
+
+/// The atomic state changes receiver
+pub struct StateReceiver<T: Clone + Send + Sync> {
+    pub(super) wrap: Arc<StateWrap<T>>,
+    pub(super) version: u64,
+}
+
+impl<T: Clone + Send + Sync> StateReceiver<T> {
+    /// Returns the last seen state version
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns true if the state is closed (e.g. removed from StateMap)
+    pub fn is_closed(&self) -> bool {
+        self.wrap.is_closed()
+    }
+
+    /// Returns true if state was changed since last seen version
+    pub fn has_changed(&self) -> bool {
+        self.wrap.version() != self.version
+    }
+
+    /// Returns an actual state value and marks it as seen
+    pub fn borrow_and_update(&mut self) -> Arc<T> {
+        self.version = self.wrap.version();
+        self.wrap.swap.load_full()
+    }
+
+    /// Returns a new state value if it was changed since last seen version
+    pub fn try_changed(&mut self) -> Option<Arc<T>> {
+        if self.has_changed() {
+            Some(self.borrow_and_update())
+        } else {
+            None
+        }
+    }
+
+    /// Waits for state change & returns a new value (returns None once the state is closed)
+    pub async fn changed(&mut self) -> Option<Arc<T>> {
+        let wrap = self.wrap.clone();
+
+        loop {
+            // subscribe before checking to avoid missing the notification:
+            let notified = wrap.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.try_changed() {
+                return Some(value);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            notified.await;
+        }
+    }
+
+    /// Waits for state change & returns a new value (with synchronously blocking)
+    pub fn blocking_changed(&mut self) -> Option<Arc<T>> {
+        let mut lock = self.wrap.mutex.lock().expect(ERR_MSG);
+
+        while !self.has_changed() && !self.is_closed() {
+            lock = self.wrap.condvar.wait(lock).expect(ERR_MSG);
+        }
+        drop(lock);
+
+        self.try_changed()
+    }
+}
+
+impl<T: Clone + Send + Sync> ::std::clone::Clone for StateReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            wrap: self.wrap.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + Debugging> ::std::fmt::Debug for StateReceiver<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.wrap.swap.load_full())
+    }
+}
@@ -0,0 +1,212 @@
+use super::{ERR_MSG, State, StateWrap};
+use crate::prelude::*;
+use std::{collections::VecDeque, time::SystemTime};
+
+/// The state value snapshot
+#[derive(Debug)]
+pub struct Snapshot<T> {
+    pub version: u64,
+    pub time: SystemTime,
+    pub value: Arc<T>,
+}
+
+impl<T> ::std::clone::Clone for Snapshot<T> {
+    fn clone(&self) -> Self {
+        Self {
+            version: self.version,
+            time: self.time,
+            value: self.value.clone(),
+        }
+    }
+}
+
+/// The ring buffer of state snapshots
+pub(super) struct History<T> {
+    capacity: usize,
+    snapshots: VecDeque<Snapshot<T>>,
+    cursor: usize,
+}
+
+impl<T> History<T> {
+    /// Creates a new history with the initial value
+    pub fn new(capacity: usize, value: Arc<T>) -> Self {
+        let mut snapshots = VecDeque::with_capacity(capacity);
+        snapshots.push_back(Snapshot {
+            version: 0,
+            time: SystemTime::now(),
+            value,
+        });
+
+        Self {
+            capacity,
+            snapshots,
+            cursor: 0,
+        }
+    }
+
+    /// Records a new snapshot (drops the undone snapshots)
+    pub fn record(&mut self, version: u64, value: Arc<T>) {
+        self.snapshots.truncate(self.cursor + 1);
+        self.snapshots.push_back(Snapshot {
+            version,
+            time: SystemTime::now(),
+            value,
+        });
+
+        if self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.cursor = self.snapshots.len() - 1;
+    }
+
+    /// Moves cursor to the previous snapshot
+    pub fn undo(&mut self) -> Option<Arc<T>> {
+        self.cursor = self.cursor.checked_sub(1)?;
+        Some(self.snapshots[self.cursor].value.clone())
+    }
+
+    /// Moves cursor to the next snapshot
+    pub fn redo(&mut self) -> Option<Arc<T>> {
+        if self.cursor + 1 >= self.snapshots.len() {
+            return None;
+        }
+        self.cursor += 1;
+        Some(self.snapshots[self.cursor].value.clone())
+    }
+}
+
+/// The atomic state with history of values
+pub struct VersionedState<T: Clone + Send + Sync> {
+    state: State<T>,
+}
+
+impl<T: Default + Clone + Send + Sync> VersionedState<T> {
+    /// Creates a new state which keeps the last 'capacity' values
+    pub const fn new(capacity: usize) -> Self {
+        Self::with_init(capacity, T::default)
+    }
+}
+
+impl<T: Clone + Send + Sync> VersionedState<T> {
+    /// Creates a new state with lazily computed initial value which keeps the last 'capacity' values
+    pub const fn with_init(capacity: usize, init: fn() -> T) -> Self {
+        // the actual value is a part of history too:
+        let capacity = if capacity > 0 { capacity } else { 1 };
+
+        Self {
+            state: State::with_history(init, capacity),
+        }
+    }
+
+    /// Returns the state snapshots (from oldest to newest)
+    pub fn history(&self) -> Vec<Snapshot<T>> {
+        self.history_lock().snapshots.iter().cloned().collect()
+    }
+
+    /// Returns the actual state snapshot
+    pub fn current(&self) -> Snapshot<T> {
+        let history = self.history_lock();
+        history.snapshots[history.cursor].clone()
+    }
+
+    /// Returns true if there is a previous value
+    pub fn can_undo(&self) -> bool {
+        self.history_lock().cursor > 0
+    }
+
+    /// Returns true if there is an undone value
+    pub fn can_redo(&self) -> bool {
+        let history = self.history_lock();
+        history.cursor + 1 < history.snapshots.len()
+    }
+
+    /// Restores the previous value (returns None if history is over)
+    pub async fn undo(&self) -> Option<Arc<T>> {
+        self.wrap().acquire().await;
+        self.undo_locked()
+    }
+
+    /// Restores the previous value (with synchronously blocking)
+    pub fn blocking_undo(&self) -> Option<Arc<T>> {
+        self.wrap().blocking_acquire();
+        self.undo_locked()
+    }
+
+    /// Restores the undone value (returns None if there is no undone values)
+    pub async fn redo(&self) -> Option<Arc<T>> {
+        self.wrap().acquire().await;
+        self.redo_locked()
+    }
+
+    /// Restores the undone value (with synchronously blocking)
+    pub fn blocking_redo(&self) -> Option<Arc<T>> {
+        self.wrap().blocking_acquire();
+        self.redo_locked()
+    }
+
+    /// Restores the previous value & releases the state lock
+    fn undo_locked(&self) -> Option<Arc<T>> {
+        let value = self.history_lock().undo();
+        self.restore(value)
+    }
+
+    /// Restores the undone value & releases the state lock
+    fn redo_locked(&self) -> Option<Arc<T>> {
+        let value = self.history_lock().redo();
+        self.restore(value)
+    }
+
+    /// Publishes the restored value & releases the state lock
+    fn restore(&self, value: Option<Arc<T>>) -> Option<Arc<T>> {
+        let wrap = self.wrap();
+
+        if let Some(value) = &value {
+            wrap.swap.store(value.clone());
+            wrap.restored();
+        }
+        wrap.release();
+
+        value
+    }
+
+    /// Returns the state wrapper
+    fn wrap(&self) -> &Arc<StateWrap<T>> {
+        self.state.wrap()
+    }
+
+    /// Locks the state history
+    fn history_lock(&self) -> MutexGuard<'_, History<T>> {
+        self.wrap()
+            .history
+            .as_ref()
+            .expect("The history is always enabled for versioned state")
+            .lock()
+            .expect(ERR_MSG)
+    }
+}
+
+impl<T: Default + Clone + Send + Sync> ::std::default::Default for VersionedState<T> {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<T: Clone + Send + Sync> ::std::ops::Deref for VersionedState<T> {
+    type Target = State<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<T: Clone + Send + Sync + Debugging> ::std::fmt::Debug for VersionedState<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.state)
+    }
+}
+
+impl<T: Clone + Send + Sync + Displaying> ::std::fmt::Display for VersionedState<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &self.state)
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Projection, State, StateReceiver, StateWrap};
+use crate::prelude::*;
+
+/// The read-only handle of atomic state
+pub struct StateView<T: Clone + Send + Sync> {
+    pub(super) wrap: Arc<StateWrap<T>>,
+}
+
+impl<T: Clone + Send + Sync> State<T> {
+    /// Returns a read-only handle of state
+    pub fn view(&self) -> StateView<T> {
+        StateView {
+            wrap: self.wrap().clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync> StateView<T> {
+    /// Returns true if data locked by some StateGuard
+    pub fn is_locked(&self) -> bool {
+        self.wrap.lock.is_true()
+    }
+
+    /// Returns a state value
+    pub async fn get(&self) -> Arc<T> {
+        while self.is_locked() {
+            self.wrap.wait_unlock().await;
+        }
+        self.dirty_get()
+    }
+
+    /// Returns a state value (with synchronously blocking)
+    pub fn blocking_get(&self) -> Arc<T> {
+        while self.is_locked() {
+            self.wrap.blocking_wait_unlock();
+        }
+        self.dirty_get()
+    }
+
+    /// Returns a state value (warning: may not contain actual data)
+    pub fn dirty_get(&self) -> Arc<T> {
+        self.wrap.swap.load_full()
+    }
+
+    /// Returns a clone of state value
+    pub async fn get_cloned(&self) -> T {
+        self.get().await.as_ref().clone()
+    }
+
+    /// Returns a clone of state value (with synchronously blocking)
+    pub fn blocking_get_cloned(&self) -> T {
+        self.blocking_get().as_ref().clone()
+    }
+
+    /// Returns a clone of state value (warning: may not contain actual data)
+    pub fn dirty_get_cloned(&self) -> T {
+        self.dirty_get().as_ref().clone()
+    }
+
+    /// Returns the state version (increments on each published change)
+    pub fn version(&self) -> u64 {
+        self.wrap.version()
+    }
+
+    /// Returns a receiver of state changes
+    pub fn subscribe(&self) -> StateReceiver<T> {
+        StateReceiver {
+            wrap: self.wrap.clone(),
+            version: self.wrap.version(),
+        }
+    }
+
+    /// Returns a read-only projection to the part of state
+    pub fn project<U>(&self, get: fn(&T) -> &U) -> Projection<T, U> {
+        Projection {
+            view: self.clone(),
+            get,
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync> ::std::clone::Clone for StateView<T> {
+    fn clone(&self) -> Self {
+        Self {
+            wrap: self.wrap.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + Debugging> ::std::fmt::Debug for StateView<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{:?}", &self.dirty_get())
+    }
+}
+
+impl<T: Clone + Send + Sync + Displaying> ::std::fmt::Display for StateView<T> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{}", &self.dirty_get())
+    }
+}
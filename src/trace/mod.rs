@@ -1,4 +1,4 @@
-use crate::{flag::Flag, prelude::*, state::State};
+use crate::{prelude::*, state::StateQueue};
 use std::{
     collections::VecDeque,
     path::{Path, PathBuf},
@@ -16,8 +16,7 @@ use tokio::{
 pub struct Trace {
     path: PathBuf,
     file: Arc<Mutex<File>>,
-    stack: Arc<State<VecDeque<String>>>,
-    available: Arc<Flag>,
+    stack: Arc<StateQueue<String>>,
     _reader_handle: Arc<JoinHandle<()>>,
 }
 
@@ -41,17 +40,13 @@ impl Trace {
             let f = File::open(&file_path).await.map_err(Error::OpenFile)?;
             Arc::new(Mutex::new(f))
         };
-        let stack = Arc::new(State::from(VecDeque::with_capacity(5)));
-        let available = Arc::new(Flag::from(false));
+        let stack = Arc::new(StateQueue::new());
 
         // read already existing data:
         if !only_new {
             let mut f = file.lock().await;
             if let Ok(initial_lines) = Self::read_new_lines(&mut f).await {
-                stack.lock().await.extend(initial_lines);
-                if !stack.lock().await.is_empty() {
-                    available.set(true);
-                }
+                stack.extend(initial_lines).await;
             }
         }
         // or just set cursor to file end:
@@ -64,7 +59,6 @@ impl Trace {
         let path_clone = path.clone();
         let file_clone = file.clone();
         let stack_clone = stack.clone();
-        let available_clone = available.clone();
 
         // spawn background file monitoring task:
         let reader_handle = tokio::spawn(async move {
@@ -85,10 +79,7 @@ impl Trace {
                         if let Ok(new_lines) = Self::read_new_lines(&mut file).await
                             && !new_lines.is_empty()
                         {
-                            stack_clone.lock().await.extend(new_lines);
-                            if available_clone.is_false() {
-                                available_clone.set(true);
-                            }
+                            stack_clone.extend(new_lines).await;
                         }
                         last_mod = mod_time;
                     }
@@ -102,42 +93,25 @@ impl Trace {
             path,
             file,
             stack,
-            available,
             _reader_handle: Arc::new(reader_handle),
         })
     }
 
     /// Fast checks for a new line available
     pub async fn check(&self) -> Option<Vec<String>> {
-        let mut lines = vec![];
-        while let Some(line) = self.stack.lock().await.pop_front() {
-            lines.push(line);
-        }
+        let lines = self.stack.drain();
 
         if lines.is_empty() { None } else { Some(lines) }
     }
 
     /// Reads next line from stack (waits until available)
     pub async fn next(&self) -> Option<Vec<String>> {
-        loop {
-            // wait until new lines are available:
-            while self.available.is_false() {
-                self.available.wait(true).await;
-            }
+        // wait until new lines are available:
+        let mut lines = vec![self.stack.pop().await];
 
-            // get line from stack:
-            let mut lines = vec![];
-            while let Some(line) = self.stack.lock().await.pop_front() {
-                lines.push(line);
-            }
-
-            // stack empty - reset flag:
-            if lines.is_empty() {
-                self.available.set(false);
-            } else {
-                return Some(lines);
-            }
-        }
+        // get other lines from stack:
+        lines.extend(self.stack.drain());
+        Some(lines)
     }
 
     /// Reads entire file content as Vec<String>